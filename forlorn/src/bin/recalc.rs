@@ -0,0 +1,443 @@
+//! recalculates pp, best statuses & stats in bulk, then reseeds the leaderboards.
+//!
+//! ```text
+//! recalc [--md5 <md5>] [--set-id <id>] [--mode <mode>] [--user <id>]
+//!        [--from <yyyy-mm-dd>] [--to <yyyy-mm-dd>]
+//!        [--concurrency <n>] [--dry-run] [--resume] [--progress <path>]
+//! ```
+//!
+//! `--from` & `--to` are both inclusive, `--to 2025-01-31` covers all of the 31st.
+//!
+//! work is split per (user, mode), each one committed in its own transaction,
+//! so an interrupted run can be picked up again with `--resume`.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use dotenvy::dotenv;
+use forlorn::{
+    config::Config,
+    constants::SubmissionStatus,
+    infrastructure::{
+        database::{self, DbPoolManager},
+        performance::Performance,
+        redis::{self, RedisConnectionManager},
+    },
+    models::Score,
+    repository::{self, score::ScoreFilter},
+    usecases::{
+        leaderboard::invalidate_map, score::calculate_score_performance, stats::recalculate,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_PROGRESS_PATH: &str = "recalc.progress.json";
+
+/// how many (user, mode) units are fetched per worker in one batch.
+const UNITS_PER_WORKER: usize = 4;
+
+const USAGE: &str = "usage: recalc [--md5 <md5>] [--set-id <id>] [--mode <mode>] [--user <id>]
+              [--from <yyyy-mm-dd>] [--to <yyyy-mm-dd>]
+              [--concurrency <n>] [--dry-run] [--resume] [--progress <path>]";
+
+struct Args {
+    filter: ScoreFilter,
+    concurrency: usize,
+    dry_run: bool,
+    resume: bool,
+    progress_path: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Progress {
+    /// the filter the run was started with, resuming with another one is refused.
+    filter: String,
+    /// last (user, mode) that was fully committed.
+    last_unit: (i32, i32),
+    /// modes whose leaderboards need a reseed at the end.
+    modes: BTreeSet<i32>,
+    finished: bool,
+}
+
+impl Progress {
+    fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+struct Context {
+    config: Arc<Config>,
+    performance: Performance,
+    db: DbPoolManager,
+    redis: RedisConnectionManager,
+    filter: ScoreFilter,
+    semaphore: Semaphore,
+    /// units holding a transaction, kept under the pool size
+    /// so the pp calculations can still get a connection.
+    transactions: Semaphore,
+    dry_run: bool,
+}
+
+#[derive(Default)]
+struct UnitReport {
+    user_id: i32,
+    mode: i32,
+    scores: usize,
+    failed: usize,
+    /// (score id, old pp, new pp)
+    pp_changes: Vec<(u64, f32, f32)>,
+    status_changes: usize,
+    /// total pp before & after, if the user has a stats row.
+    stats_pp: Option<(u32, u32)>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let Some(args) = parse_args()? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    let config = Arc::new(Config::from_env()?);
+
+    let db = database::create_pool(&config.database).await?;
+    let (redis, _, _) = redis::create_connection(&config.redis).await?;
+
+    let filter_key = format!("{:?}", args.filter);
+
+    let mut progress = if args.resume && args.progress_path.exists() {
+        let progress = Progress::load(&args.progress_path)?;
+
+        if progress.filter != filter_key {
+            bail!(
+                "{} belongs to another run ({}), refusing to resume",
+                args.progress_path.display(),
+                progress.filter
+            );
+        }
+
+        tracing::info!(
+            "resuming after user {} mode {}",
+            progress.last_unit.0,
+            progress.last_unit.1
+        );
+
+        progress
+    } else {
+        Progress { filter: filter_key, ..Default::default() }
+    };
+
+    let max_transactions = (config.database.max_connections as usize)
+        .saturating_sub(1)
+        .max(1);
+
+    let ctx = Arc::new(Context {
+        performance: Performance::from_config(&config),
+        config,
+        db,
+        redis,
+        filter: args.filter,
+        semaphore: Semaphore::new(args.concurrency),
+        transactions: Semaphore::new(max_transactions),
+        dry_run: args.dry_run,
+    });
+
+    let mut units_done = 0;
+    let mut pp_changes = 0;
+    let mut status_changes = 0;
+    let mut failed = 0;
+
+    while !progress.finished {
+        let units = repository::score::fetch_recalc_units(
+            &ctx.db,
+            &ctx.filter,
+            progress.last_unit,
+            (args.concurrency * UNITS_PER_WORKER) as u32,
+        )
+        .await?;
+
+        let Some(last_unit) = units.last().copied() else {
+            progress.finished = true;
+            break;
+        };
+
+        let mut workers = JoinSet::new();
+
+        for (user_id, mode) in units.iter().copied() {
+            let ctx = ctx.clone();
+            workers.spawn(async move { recalc_unit(&ctx, user_id, mode).await });
+        }
+
+        // a failed unit stops the run before the checkpoint moves past it,
+        // everything in the batch is safe to redo.
+        while let Some(result) = workers.join_next().await {
+            let report = result??;
+
+            units_done += 1;
+            pp_changes += report.pp_changes.len();
+            status_changes += report.status_changes;
+            failed += report.failed;
+
+            print_report(&report, ctx.dry_run);
+        }
+
+        progress.last_unit = last_unit;
+        progress.modes.extend(units.iter().map(|(_, mode)| *mode));
+
+        if !ctx.dry_run {
+            progress.save(&args.progress_path)?;
+        }
+    }
+
+    for mode in &progress.modes {
+        let entries = repository::stats::fetch_leaderboard_entries(&ctx.db, *mode).await?;
+
+        if ctx.dry_run {
+            println!(
+                "[dry-run] would reseed bancho:leaderboard:{mode} with {} players",
+                entries.len()
+            );
+            continue;
+        }
+
+        repository::stats::reseed_leaderboards(&ctx.redis, *mode, &entries).await?;
+
+        tracing::info!(
+            "reseeded bancho:leaderboard:{mode} with {} players",
+            entries.len()
+        );
+    }
+
+    if !ctx.dry_run && args.progress_path.exists() {
+        std::fs::remove_file(&args.progress_path)?;
+    }
+
+    println!(
+        "{}{units_done} users, {pp_changes} pp changes, {status_changes} status changes, {failed} failed calculations",
+        if ctx.dry_run { "[dry-run] " } else { "" },
+    );
+
+    if failed > 0 {
+        println!(
+            "scores that failed kept their old pp, run again with the same filter to retry them."
+        );
+    }
+
+    Ok(())
+}
+
+async fn recalc_unit(ctx: &Arc<Context>, user_id: i32, mode: i32) -> Result<UnitReport> {
+    let scores = repository::score::fetch_for_recalc(&ctx.db, &ctx.filter, user_id, mode).await?;
+
+    let mut report = UnitReport {
+        user_id,
+        mode,
+        scores: scores.len(),
+        ..Default::default()
+    };
+
+    let mut calculations = JoinSet::new();
+
+    for score in scores {
+        let ctx = ctx.clone();
+        calculations.spawn(async move {
+            let result = calculate_pp(&ctx, &score).await;
+            (score, result)
+        });
+    }
+
+    let mut maps = BTreeSet::new();
+
+    while let Some(result) = calculations.join_next().await {
+        let (score, result) = result?;
+
+        match result {
+            Ok(pp) => {
+                if pp != score.pp {
+                    report.pp_changes.push((score.id, score.pp, pp));
+                }
+
+                maps.insert(score.map_md5);
+            },
+            Err(e) => {
+                tracing::warn!("pp calculation failed for score {}: {e}", score.id);
+                report.failed += 1;
+            },
+        }
+    }
+
+    report.pp_changes.sort_by_key(|(id, _, _)| *id);
+
+    let _permit = ctx.transactions.acquire().await?;
+    let mut tx = ctx.db.begin().await?;
+
+    for (score_id, _, pp) in &report.pp_changes {
+        repository::score::update_pp(&mut tx, *score_id, *pp).await?;
+    }
+
+    for map_md5 in &maps {
+        let passed =
+            repository::score::fetch_passed_on_map(&mut tx, user_id, map_md5, mode).await?;
+
+        // same rule as `calculate_status`, a score only takes over with strictly more pp.
+        // stable & lazer each keep their own best.
+        let best_id = |lazer: bool| {
+            passed
+                .iter()
+                .filter(|&&(_, _, _, is_lazer)| is_lazer == lazer)
+                .fold(None::<(u64, f32)>, |best, &(id, pp, _, _)| match best {
+                    Some((_, best_pp)) if pp <= best_pp => best,
+                    _ => Some((id, pp)),
+                })
+                .map(|(id, _)| id)
+        };
+        let best_ids = [best_id(false), best_id(true)];
+
+        for (score_id, _, status, _) in &passed {
+            let expected = if best_ids.contains(&Some(*score_id)) {
+                SubmissionStatus::Best
+            } else {
+                SubmissionStatus::Submitted
+            };
+
+            if *status != expected.as_i32() {
+                repository::score::update_status(&mut tx, *score_id, expected.as_i32()).await?;
+                report.status_changes += 1;
+            }
+        }
+    }
+
+    if let Some(mut stats) =
+        repository::stats::fetch_for_update(&mut tx, &ctx.redis, user_id, mode).await?
+    {
+        let pp_before = stats.pp;
+
+        (
+            stats.rscore,
+            stats.xh_count,
+            stats.x_count,
+            stats.sh_count,
+            stats.s_count,
+            stats.a_count,
+        ) = repository::stats::fetch_ranked_totals(&mut tx, &stats).await?;
+
+        recalculate(&mut tx, &mut stats).await?;
+        repository::stats::save_ranked(&mut tx, &stats).await?;
+
+        report.stats_pp = Some((pp_before, stats.pp));
+    }
+
+    // a dry run goes through every query, it just never commits them.
+    if ctx.dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+
+        for map_md5 in &maps {
+            invalidate_map(&ctx.redis, map_md5).await;
+        }
+    }
+
+    Ok(report)
+}
+
+async fn calculate_pp(ctx: &Context, score: &Score) -> Result<f32> {
+    let _permit = ctx.semaphore.acquire().await?;
+
+    let beatmap = repository::beatmap::fetch_by_md5(&ctx.config, &ctx.db, &score.map_md5)
+        .await?
+        .ok_or_else(|| anyhow!("beatmap {} not found", score.map_md5))?;
+
+    let (pp, _, _) = calculate_score_performance(&ctx.performance, score, &beatmap).await?;
+
+    Ok(pp)
+}
+
+fn print_report(report: &UnitReport, dry_run: bool) {
+    let stats = match report.stats_pp {
+        Some((before, after)) => format!("{before}pp -> {after}pp"),
+        None => "no stats".to_string(),
+    };
+
+    if !dry_run {
+        tracing::info!(
+            "user {} mode {}: {} scores, {} pp changes, {} status changes, {}",
+            report.user_id,
+            report.mode,
+            report.scores,
+            report.pp_changes.len(),
+            report.status_changes,
+            stats,
+        );
+        return;
+    }
+
+    println!(
+        "[dry-run] user {} mode {}: {} scores, {} status changes, {}",
+        report.user_id, report.mode, report.scores, report.status_changes, stats,
+    );
+
+    for (score_id, old, new) in &report.pp_changes {
+        println!("[dry-run]   score {score_id}: {old:.2}pp -> {new:.2}pp");
+    }
+}
+
+fn parse_args() -> Result<Option<Args>> {
+    let mut args = Args {
+        filter: ScoreFilter::default(),
+        concurrency: DEFAULT_CONCURRENCY,
+        dry_run: false,
+        resume: false,
+        progress_path: PathBuf::from(DEFAULT_PROGRESS_PATH),
+    };
+
+    let mut argv = std::env::args().skip(1);
+
+    while let Some(arg) = argv.next() {
+        let mut value = || argv.next().ok_or_else(|| anyhow!("{arg} expects a value"));
+
+        match arg.as_str() {
+            "--md5" => args.filter.map_md5 = Some(value()?),
+            "--set-id" => args.filter.set_id = Some(value()?.parse()?),
+            "--mode" => args.filter.mode = Some(value()?.parse()?),
+            "--user" => args.filter.user_id = Some(value()?.parse()?),
+            "--from" => args.filter.from = Some(parse_date(&value()?)?),
+            // the filter's end is exclusive, so it's the start of the next day
+            "--to" => args.filter.to = Some(parse_date(&value()?)? + TimeDelta::days(1)),
+            "--concurrency" => args.concurrency = value()?.parse::<usize>()?.max(1),
+            "--progress" => args.progress_path = PathBuf::from(value()?),
+            "--dry-run" => args.dry_run = true,
+            "--resume" => args.resume = true,
+            "-h" | "--help" => return Ok(None),
+            _ => bail!("unknown argument {arg}\n{USAGE}"),
+        }
+    }
+
+    Ok(Some(args))
+}
+
+fn parse_date(value: &str) -> Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")?;
+
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
@@ -0,0 +1,133 @@
+//! moves the lazer payload out of replays that were stored with it glued
+//! onto the compressed frames, so every stored .osr is readable again.
+//!
+//! ```text
+//! split_replays [--after <score id>] [--batch <n>] [--dry-run]
+//! ```
+//!
+//! replays without a payload are left alone, so it's safe to run again.
+
+use anyhow::{Result, anyhow, bail};
+use dotenvy::dotenv;
+use forlorn::{config::Config, infrastructure::database, replay::split_lazer_payload, repository};
+use storage::Storage;
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_BATCH: u32 = 500;
+
+const USAGE: &str = "usage: split_replays [--after <score id>] [--batch <n>] [--dry-run]";
+
+struct Args {
+    after: u64,
+    batch: u32,
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let Some(args) = parse_args()? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    let config = Config::from_env()?;
+    let db = database::create_pool(&config.database).await?;
+
+    let storage = Storage::new(
+        config.replay_path.clone(),
+        config.screenshot_path.clone(),
+        config.osz_path.clone(),
+        &config.r2.bucket,
+        &format!("https://{}.r2.cloudflarestorage.com", config.r2.account_id),
+        &config.r2.access_key,
+        &config.r2.secret_key,
+    )
+    .await;
+
+    let mut after = args.after;
+    let mut checked = 0;
+    let mut split = 0;
+    let mut failed = 0;
+
+    loop {
+        let ids = repository::score::fetch_ids_with_replay(&db, after, args.batch).await?;
+
+        let Some(last) = ids.last().copied() else {
+            break;
+        };
+
+        for score_id in ids {
+            let Ok(data) = storage.load_replay(score_id).await else {
+                continue;
+            };
+
+            checked += 1;
+
+            let (replay, payload) = match split_lazer_payload(&data) {
+                Ok(Some(parts)) => parts,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("score {score_id} doesn't have a readable replay: {e}");
+                    failed += 1;
+                    continue;
+                },
+            };
+
+            split += 1;
+
+            if args.dry_run {
+                println!(
+                    "[dry-run] score {score_id}: {} bytes of replay, {} bytes of payload",
+                    replay.len(),
+                    payload.len()
+                );
+                continue;
+            }
+
+            // payload first, so an interrupted run never loses it.
+            storage.save_lazer_replay(score_id, payload).await?;
+            storage.save_replay(score_id, replay).await?;
+        }
+
+        after = last;
+
+        tracing::info!("done up to score {after} ({split} split so far)");
+    }
+
+    println!(
+        "{}{checked} replays checked, {split} split, {failed} unreadable",
+        if args.dry_run { "[dry-run] " } else { "" },
+    );
+
+    Ok(())
+}
+
+fn parse_args() -> Result<Option<Args>> {
+    let mut args = Args {
+        after: 0,
+        batch: DEFAULT_BATCH,
+        dry_run: false,
+    };
+
+    let mut argv = std::env::args().skip(1);
+
+    while let Some(arg) = argv.next() {
+        let mut value = || argv.next().ok_or_else(|| anyhow!("{arg} expects a value"));
+
+        match arg.as_str() {
+            "--after" => args.after = value()?.parse()?,
+            "--batch" => args.batch = value()?.parse::<u32>()?.max(1),
+            "--dry-run" => args.dry_run = true,
+            "-h" | "--help" => return Ok(None),
+            _ => bail!("unknown argument {arg}\n{USAGE}"),
+        }
+    }
+
+    Ok(Some(args))
+}
@@ -17,6 +17,17 @@ pub struct Config {
     pub omajinai: OmajinaiConfig,
     pub webhook: DiscordWebhookConfig,
     pub osu: OsuConfig,
+    pub outbox: OutboxConfig,
+    pub performance: PerformanceConfig,
+    pub score_validation: ScoreValidationConfig,
+    /// how many maps are kept in memory at most.
+    pub beatmap_cache_size: usize,
+    /// how many scores the client gets on a leaderboard.
+    pub leaderboard_size: u32,
+    /// acronyms of the mods that don't split the mods leaderboard.
+    pub leaderboard_merged_mods: String,
+    /// required by the admin api, which is disabled when empty.
+    pub admin_key: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +68,8 @@ pub struct CloudflareR2Config {
 pub struct OmajinaiConfig {
     pub base_url: String,
     pub beatmap_service_url: String,
+    /// where the .osu files are cached, shared with omajinai.
+    pub beatmap_path: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +86,34 @@ pub struct OsuConfig {
     //pub client_secret: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxConfig {
+    /// "database" or "memory", the latter never touches redis.
+    pub backend: String,
+    pub max_attempts: i32,
+    pub poll_interval_ms: u64,
+    /// how long delivered events are kept around.
+    pub retention_days: i64,
+}
+
+/// "reject", "flag" or "restrict" for each of the submission checks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreValidationConfig {
+    pub judgement_count: String,
+    pub combo: String,
+    pub perfect: String,
+    pub grade: String,
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceConfig {
+    /// "omajinai" or "local".
+    pub primary: String,
+    /// used when the primary fails, empty to not fall back at all.
+    pub fallback: String,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -88,6 +129,13 @@ impl Default for Config {
             omajinai: OmajinaiConfig::default(),
             webhook: DiscordWebhookConfig::default(),
             osu: OsuConfig::default(),
+            outbox: OutboxConfig::default(),
+            performance: PerformanceConfig::default(),
+            score_validation: ScoreValidationConfig::default(),
+            beatmap_cache_size: 10_000,
+            leaderboard_size: 50,
+            leaderboard_merged_mods: "NC,PF,TD,V2".into(),
+            admin_key: String::new(),
         }
     }
 }
@@ -143,6 +191,7 @@ impl Default for OmajinaiConfig {
         Self {
             base_url: "http://localhost:9292".into(),
             beatmap_service_url: String::new(),
+            beatmap_path: PathBuf::from(".data/osu"),
         }
     }
 }
@@ -162,6 +211,38 @@ impl Default for OsuConfig {
     }
 }
 
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            backend: "database".into(),
+            max_attempts: 10,
+            poll_interval_ms: 1000,
+            retention_days: 7,
+        }
+    }
+}
+
+impl Default for ScoreValidationConfig {
+    fn default() -> Self {
+        Self {
+            judgement_count: "flag".into(),
+            combo: "flag".into(),
+            perfect: "flag".into(),
+            grade: "flag".into(),
+            checksum: "flag".into(),
+        }
+    }
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            primary: "omajinai".into(),
+            fallback: String::new(),
+        }
+    }
+}
+
 impl Config {
     pub fn from_env() -> Result<Self> {
         let mut config = Config::default();
@@ -246,6 +327,9 @@ impl Config {
         if let Ok(omajinai_beatmap_service_url) = std::env::var("OMAJINAI_BEATMAP_SERVICE_URL") {
             config.omajinai.beatmap_service_url = omajinai_beatmap_service_url;
         }
+        if let Ok(omajinai_beatmap_path) = std::env::var("OMAJINAI_BEATMAP_PATH") {
+            config.omajinai.beatmap_path = PathBuf::from(omajinai_beatmap_path);
+        }
         if let Ok(discord_score_webhook) = std::env::var("DISCORD_SCORE_WEBHOOK") {
             config.webhook.score = discord_score_webhook;
         }
@@ -257,6 +341,58 @@ impl Config {
             config.osu.api_key = osu_api_key;
         }
 
+        if let Ok(outbox_backend) = std::env::var("OUTBOX_BACKEND") {
+            config.outbox.backend = outbox_backend;
+        }
+        if let Ok(outbox_max_attempts) = std::env::var("OUTBOX_MAX_ATTEMPTS") {
+            config.outbox.max_attempts = outbox_max_attempts.parse()?;
+        }
+        if let Ok(outbox_poll_interval) = std::env::var("OUTBOX_POLL_INTERVAL_MS") {
+            config.outbox.poll_interval_ms = outbox_poll_interval.parse()?;
+        }
+        if let Ok(outbox_retention_days) = std::env::var("OUTBOX_RETENTION_DAYS") {
+            config.outbox.retention_days = outbox_retention_days.parse()?;
+        }
+
+        if let Ok(performance_primary) = std::env::var("PERFORMANCE_PRIMARY") {
+            config.performance.primary = performance_primary;
+        }
+        if let Ok(performance_fallback) = std::env::var("PERFORMANCE_FALLBACK") {
+            config.performance.fallback = performance_fallback;
+        }
+
+        if let Ok(judgement_count) = std::env::var("SCORE_VALIDATION_JUDGEMENT_COUNT") {
+            config.score_validation.judgement_count = judgement_count;
+        }
+        if let Ok(combo) = std::env::var("SCORE_VALIDATION_COMBO") {
+            config.score_validation.combo = combo;
+        }
+        if let Ok(perfect) = std::env::var("SCORE_VALIDATION_PERFECT") {
+            config.score_validation.perfect = perfect;
+        }
+        if let Ok(grade) = std::env::var("SCORE_VALIDATION_GRADE") {
+            config.score_validation.grade = grade;
+        }
+        if let Ok(checksum) = std::env::var("SCORE_VALIDATION_CHECKSUM") {
+            config.score_validation.checksum = checksum;
+        }
+
+        if let Ok(beatmap_cache_size) = std::env::var("BEATMAP_CACHE_SIZE") {
+            config.beatmap_cache_size = beatmap_cache_size.parse()?;
+        }
+
+        if let Ok(leaderboard_size) = std::env::var("LEADERBOARD_SIZE") {
+            config.leaderboard_size = leaderboard_size.parse()?;
+        }
+
+        if let Ok(leaderboard_merged_mods) = std::env::var("LEADERBOARD_MERGED_MODS") {
+            config.leaderboard_merged_mods = leaderboard_merged_mods;
+        }
+
+        if let Ok(admin_key) = std::env::var("ADMIN_KEY") {
+            config.admin_key = admin_key;
+        }
+
         Ok(config)
     }
 }
@@ -44,4 +44,19 @@ impl Grade {
             _ => Grade::N,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Grade::N => "N",
+            Grade::F => "F",
+            Grade::D => "D",
+            Grade::C => "C",
+            Grade::B => "B",
+            Grade::A => "A",
+            Grade::S => "S",
+            Grade::SH => "SH",
+            Grade::X => "X",
+            Grade::XH => "XH",
+        }
+    }
 }
@@ -3,3 +3,6 @@ pub const REFX_CURRENT_CLIENT_HASH: &str = "30a2624d8f0d4d8120f33eb0d454f54b";
 
 /// md5
 pub const REFX_AUTH_HASH: &str = "69906d8897a67a88beaf51020daac499";
+
+/// scores.online_checksum of every score submitted from lazer
+pub const LAZER_ONLINE_CHECKSUM: &str = "lazer_score";
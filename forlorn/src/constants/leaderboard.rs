@@ -5,6 +5,9 @@ pub enum LeaderboardType {
     Mods = 2,
     Friends = 3,
     Country = 4,
+    // not stable tabs, the refx client asks for these directly
+    Clan = 5,
+    Tournament = 6,
 }
 
 impl LeaderboardType {
@@ -15,6 +18,8 @@ impl LeaderboardType {
             2 => LeaderboardType::Mods,
             3 => LeaderboardType::Friends,
             4 => LeaderboardType::Country,
+            5 => LeaderboardType::Clan,
+            6 => LeaderboardType::Tournament,
             _ => LeaderboardType::Local,
         }
     }
@@ -5,13 +5,17 @@ pub mod leaderboard;
 pub mod mode;
 pub mod mods;
 pub mod privileges;
+pub mod review;
 pub mod status;
+pub mod validation;
 
 pub use grade::Grade;
-pub use hashes::{REFX_AUTH_HASH, REFX_CURRENT_CLIENT_HASH};
+pub use hashes::{LAZER_ONLINE_CHECKSUM, REFX_AUTH_HASH, REFX_CURRENT_CLIENT_HASH};
 pub use lastfm::LastFmFlags;
 pub use leaderboard::LeaderboardType;
 pub use mode::GameMode;
 pub use mods::Mods;
 pub use privileges::Privileges;
-pub use status::{RankedStatus, SubmissionStatus};
+pub use review::ReviewThresholds;
+pub use status::{OutboxStatus, RankedStatus, SubmissionStatus};
+pub use validation::ViolationAction;
@@ -38,7 +38,55 @@ bitflags! {
     }
 }
 
+const ACRONYMS: [(Mods, &str); 31] = [
+    (Mods::NOFAIL, "NF"),
+    (Mods::EASY, "EZ"),
+    (Mods::TOUCHSCREEN, "TD"),
+    (Mods::HIDDEN, "HD"),
+    (Mods::HARDROCK, "HR"),
+    (Mods::SUDDENDEATH, "SD"),
+    (Mods::DOUBLETIME, "DT"),
+    (Mods::RELAX, "RX"),
+    (Mods::HALFTIME, "HT"),
+    (Mods::NIGHTCORE, "NC"),
+    (Mods::FLASHLIGHT, "FL"),
+    (Mods::AUTOPLAY, "AT"),
+    (Mods::SPUNOUT, "SO"),
+    (Mods::AUTOPILOT, "AP"),
+    (Mods::PERFECT, "PF"),
+    (Mods::KEY4, "K4"),
+    (Mods::KEY5, "K5"),
+    (Mods::KEY6, "K6"),
+    (Mods::KEY7, "K7"),
+    (Mods::KEY8, "K8"),
+    (Mods::FADEIN, "FI"),
+    (Mods::RANDOM, "RD"),
+    (Mods::CINEMA, "CN"),
+    (Mods::TARGET, "TP"),
+    (Mods::KEY9, "K9"),
+    (Mods::KEYCOOP, "KC"),
+    (Mods::KEY1, "K1"),
+    (Mods::KEY3, "K3"),
+    (Mods::KEY2, "K2"),
+    (Mods::SCOREV2, "V2"),
+    (Mods::MIRROR, "MR"),
+];
+
 impl Mods {
+    pub fn from_acronym(acronym: &str) -> Option<Mods> {
+        ACRONYMS
+            .iter()
+            .find(|(_, text)| text.eq_ignore_ascii_case(acronym))
+            .map(|(flag, _)| *flag)
+    }
+
+    /// comma separated acronyms, e.g. `NC,PF`. unknown ones are skipped.
+    pub fn from_acronyms(list: &str) -> Mods {
+        list.split(',')
+            .filter_map(|acronym| Mods::from_acronym(acronym.trim()))
+            .fold(Mods::NOMOD, |mods, flag| mods | flag)
+    }
+
     pub fn as_str(self, clock_rate: Option<f64>) -> String {
         if self.is_empty() {
             return "NM".into();
@@ -46,41 +94,7 @@ impl Mods {
 
         let mut out = String::new();
 
-        let list = [
-            (Mods::NOFAIL, "NF"),
-            (Mods::EASY, "EZ"),
-            (Mods::TOUCHSCREEN, "TD"),
-            (Mods::HIDDEN, "HD"),
-            (Mods::HARDROCK, "HR"),
-            (Mods::SUDDENDEATH, "SD"),
-            (Mods::DOUBLETIME, "DT"),
-            (Mods::RELAX, "RX"),
-            (Mods::HALFTIME, "HT"),
-            (Mods::NIGHTCORE, "NC"),
-            (Mods::FLASHLIGHT, "FL"),
-            (Mods::AUTOPLAY, "AT"),
-            (Mods::SPUNOUT, "SO"),
-            (Mods::AUTOPILOT, "AP"),
-            (Mods::PERFECT, "PF"),
-            (Mods::KEY4, "K4"),
-            (Mods::KEY5, "K5"),
-            (Mods::KEY6, "K6"),
-            (Mods::KEY7, "K7"),
-            (Mods::KEY8, "K8"),
-            (Mods::FADEIN, "FI"),
-            (Mods::RANDOM, "RD"),
-            (Mods::CINEMA, "CN"),
-            (Mods::TARGET, "TP"),
-            (Mods::KEY9, "K9"),
-            (Mods::KEYCOOP, "KC"),
-            (Mods::KEY1, "K1"),
-            (Mods::KEY3, "K3"),
-            (Mods::KEY2, "K2"),
-            (Mods::SCOREV2, "V2"),
-            (Mods::MIRROR, "MR"),
-        ];
-
-        for (flag, text) in list {
+        for (flag, text) in ACRONYMS {
             if self.contains(Mods::NIGHTCORE) && flag == Mods::DOUBLETIME {
                 continue;
             }
@@ -99,6 +113,15 @@ impl Mods {
         out
     }
 
+    /// the mods a score is ranked with on the mods leaderboard.
+    ///
+    /// `merged` don't get a board of their own. NC & PF always come with
+    /// DT & SD set so they fold into those, the rest just drops out. the
+    /// clock rate isn't part of the mods, so it never splits boards.
+    pub fn leaderboard_equivalent(self, merged: Mods) -> Mods {
+        self.difference(merged)
+    }
+
     pub fn conflict(self) -> bool {
         if self.contains(Mods::AUTOPLAY) || self.contains(Mods::CINEMA) {
             return true;
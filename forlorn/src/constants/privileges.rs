@@ -8,5 +8,11 @@ bitflags! {
         const UNRESTRICTED       = 1 << 0;
         const VERIFIED           = 1 << 1;
         const WHITELISTED        = 1 << 2;
+
+        const MODERATOR          = 1 << 12;
+        const ADMINISTRATOR      = 1 << 13;
+        const DEVELOPER          = 1 << 14;
+
+        const STAFF = Self::MODERATOR.bits() | Self::ADMINISTRATOR.bits() | Self::DEVELOPER.bits();
     }
 }
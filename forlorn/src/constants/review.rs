@@ -0,0 +1,67 @@
+use crate::constants::mode::GameMode;
+
+/// when a replay looks inhuman enough for someone to take a look at it.
+///
+/// these only flag scores for review, they never restrict anyone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReviewThresholds {
+    /// anything below this is too consistent for a human.
+    pub min_unstable_rate: Option<f32>,
+    /// same goes for how long the keys are held down (ms²).
+    pub min_hold_time_variance: Option<f32>,
+    /// presses faster than this on average (ms) are most likely a macro.
+    pub min_press_interval: Option<f32>,
+    pub max_snaps: Option<i32>,
+}
+
+impl ReviewThresholds {
+    pub fn for_mode(mode: GameMode) -> Self {
+        match mode {
+            GameMode::VN_OSU => Self {
+                min_unstable_rate: Some(50.0),
+                min_hold_time_variance: Some(10.0),
+                min_press_interval: Some(30.0),
+                max_snaps: Some(15),
+            },
+            GameMode::VN_TAIKO => Self {
+                min_unstable_rate: Some(40.0),
+                min_hold_time_variance: Some(10.0),
+                min_press_interval: Some(20.0),
+                max_snaps: None,
+            },
+            GameMode::VN_MANIA => Self {
+                min_hold_time_variance: Some(10.0),
+                min_press_interval: Some(10.0),
+                ..Default::default()
+            },
+
+            // relax presses the keys by itself, the cursor is all we've got
+            GameMode::RX_OSU => Self {
+                max_snaps: Some(15),
+                ..Default::default()
+            },
+
+            // and autopilot is the other way around
+            GameMode::AP_OSU => Self {
+                min_unstable_rate: Some(50.0),
+                min_hold_time_variance: Some(10.0),
+                min_press_interval: Some(30.0),
+                max_snaps: None,
+            },
+
+            // aim assist is allowed here, so only catch the really obvious ones
+            GameMode::CHEAT_OSU => Self {
+                max_snaps: Some(60),
+                ..Default::default()
+            },
+
+            // touch devices snap by design
+            GameMode::TOUCH_DEVICE_OSU => Self {
+                min_unstable_rate: Some(50.0),
+                ..Default::default()
+            },
+
+            _ => Self::default(),
+        }
+    }
+}
@@ -48,6 +48,17 @@ impl RankedStatus {
         *self as i32
     }
 
+    pub fn from_i32(status: i32) -> Self {
+        match status {
+            1 => RankedStatus::UpdateAvailable,
+            2 => RankedStatus::Ranked,
+            3 => RankedStatus::Approved,
+            4 => RankedStatus::Qualified,
+            5 => RankedStatus::Loved,
+            _ => RankedStatus::Pending,
+        }
+    }
+
     pub fn as_osu_api(&self) -> i32 {
         match self {
             RankedStatus::Pending => 0,
@@ -70,3 +81,26 @@ impl RankedStatus {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i8)]
+pub enum OutboxStatus {
+    Pending = 0,
+    Delivered = 1,
+    Failed = 2,
+}
+
+impl OutboxStatus {
+    pub fn as_i8(&self) -> i8 {
+        *self as i8
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(OutboxStatus::Pending),
+            "delivered" => Some(OutboxStatus::Delivered),
+            "failed" => Some(OutboxStatus::Failed),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+/// what happens to a score that fails one of the submission checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationAction {
+    /// the score never makes it in.
+    Reject,
+    /// the score goes in, staff gets a message about it.
+    Flag,
+    /// the score goes in, the player gets restricted.
+    Restrict,
+}
+
+impl ViolationAction {
+    pub fn parse(action: &str) -> Option<Self> {
+        match action {
+            "reject" => Some(ViolationAction::Reject),
+            "flag" => Some(ViolationAction::Flag),
+            "restrict" => Some(ViolationAction::Restrict),
+            _ => None,
+        }
+    }
+}
@@ -44,6 +44,10 @@ pub struct GetScores {
 
     #[serde(rename = "fx", default)]
     pub is_refx: i32,
+
+    /// opt-in, shows the lazer leaderboard instead
+    #[serde(rename = "lz", default)]
+    pub lazer: i32,
 }
 
 impl GetScores {
@@ -59,6 +63,10 @@ impl GetScores {
         self.is_refx != 0
     }
 
+    pub fn lazer(&self) -> bool {
+        self.lazer != 0
+    }
+
     pub fn mode(&self) -> GameMode {
         GameMode::from_params(self.mode, self.mods())
     }
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct GetOutboxEvents {
+    #[serde(rename = "status")]
+    pub status: Option<String>,
+
+    #[serde(rename = "limit")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayOutboxEvents {
+    /// replays every failed event when missing
+    #[serde(rename = "id")]
+    pub event_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetBeatmapCache {
+    /// how many entries to dump, most recently used first
+    #[serde(rename = "limit")]
+    pub limit: Option<usize>,
+}
+
+/// one of the selectors has to be given, the whole cache only goes with `all=true`.
+#[derive(Debug, Deserialize)]
+pub struct InvalidateBeatmapCache {
+    #[serde(rename = "md5")]
+    pub map_md5: Option<String>,
+
+    #[serde(rename = "id")]
+    pub map_id: Option<i32>,
+
+    #[serde(rename = "set_id")]
+    pub set_id: Option<i32>,
+
+    /// flushes every replica, so it has to be asked for
+    #[serde(default)]
+    pub all: bool,
+}
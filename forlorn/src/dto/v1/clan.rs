@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetClanMapRanking {
+    #[serde(rename = "md5")]
+    pub map_md5: String,
+
+    #[serde(rename = "mode", default)]
+    pub mode: i32,
+
+    /// `pp` or `score`, pp if it's left out.
+    #[serde(rename = "metric")]
+    pub metric: Option<String>,
+
+    #[serde(rename = "limit")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct GetClanStats {
+    #[serde(rename = "id")]
+    pub clan_id: i32,
+}
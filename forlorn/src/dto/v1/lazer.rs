@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazerMod {
+    pub acronym: String,
+    #[serde(default)]
+    pub settings: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitLazerScore {
+    pub username: String,
+    pub password_md5: String,
+
+    pub beatmap_md5: String,
+    pub ruleset_id: i32,
+    pub passed: bool,
+
+    /// standardised
+    pub total_score: i64,
+    pub classic_total_score: i64,
+
+    pub max_combo: i32,
+    #[serde(default)]
+    pub mods: Vec<LazerMod>,
+    /// e.g. `{"great": 727, "ok": 21, "miss": 1}`
+    pub statistics: HashMap<String, i32>,
+    pub ended_at: DateTime<Utc>,
+}
@@ -0,0 +1,46 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetLeaderboard {
+    #[serde(rename = "md5")]
+    pub map_md5: String,
+
+    #[serde(rename = "mode", default)]
+    pub mode: i32,
+
+    /// same values as the client's leaderboard types, global if left out.
+    #[serde(rename = "type", default = "default_leaderboard_type")]
+    pub leaderboard_type: i32,
+
+    #[serde(rename = "mods", default)]
+    pub mods: i32,
+
+    /// whose friends, clan or country the board is for.
+    #[serde(rename = "user")]
+    pub user_id: Option<i32>,
+
+    /// overrides the user's country on country boards.
+    #[serde(rename = "country")]
+    pub country: Option<String>,
+
+    /// `pp` or `score`, pp if it's left out.
+    #[serde(rename = "metric")]
+    pub metric: Option<String>,
+
+    #[serde(rename = "lazer", default)]
+    pub lazer: bool,
+
+    /// includes the cheat values of every score.
+    #[serde(rename = "refx", default)]
+    pub refx: bool,
+
+    #[serde(rename = "limit")]
+    pub limit: Option<u32>,
+
+    #[serde(rename = "offset", default)]
+    pub offset: u32,
+}
+
+fn default_leaderboard_type() -> i32 {
+    1
+}
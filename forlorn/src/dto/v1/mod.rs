@@ -1,2 +1,7 @@
+pub mod admin;
 pub mod calculate;
+pub mod clan;
+pub mod lazer;
+pub mod leaderboard;
 pub mod replay;
+pub mod tournament;
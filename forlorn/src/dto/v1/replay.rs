@@ -4,4 +4,20 @@ use serde::Deserialize;
 pub struct GetReplay {
     #[serde(rename = "id")]
     pub score_id: u64,
+
+    /// optional, hidden replays are only served to staff & their player.
+    #[serde(rename = "u")]
+    pub username: Option<String>,
+
+    #[serde(rename = "h")]
+    pub password_md5: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GetMostWatched {
+    #[serde(rename = "mode", default)]
+    pub mode: i32,
+
+    #[serde(rename = "limit")]
+    pub limit: Option<u32>,
 }
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetStandings {
+    #[serde(rename = "id")]
+    pub tournament_id: u32,
+
+    #[serde(rename = "mode", default)]
+    pub mode: i32,
+
+    /// `pp` or `score`, pp if it's left out.
+    #[serde(rename = "metric")]
+    pub metric: Option<String>,
+
+    #[serde(rename = "limit")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTournament {
+    pub name: String,
+    /// exact mods every score has to be set with, any when left out
+    #[serde(default)]
+    pub mods: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub maps: Vec<String>,
+}
@@ -1,13 +1,17 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
-use sqlx::{MySql, Pool, mysql::MySqlPoolOptions};
+use sqlx::{MySql, MySqlConnection, Pool, mysql::MySqlPoolOptions};
 
 use crate::config::DatabaseConfig;
 
 pub type DbPool = Pool<MySql>;
 pub type DbPoolManager = Arc<DbPool>;
 
+/// anything that has to be written together takes one of these,
+/// so it can run inside a transaction.
+pub type DbConnection = MySqlConnection;
+
 pub async fn create_pool(config: &DatabaseConfig) -> Result<DbPoolManager> {
     let database_url = format!(
         "mysql://{}:{}@{}:{}/{}",
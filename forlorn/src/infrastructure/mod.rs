@@ -1,6 +1,8 @@
 pub mod database;
 pub mod datadog;
 pub mod omajinai;
+pub mod outbox;
+pub mod performance;
 pub mod redis;
 
 pub use redis::subscriber::SubscriberHandler;
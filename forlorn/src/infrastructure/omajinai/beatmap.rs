@@ -1,6 +1,7 @@
 use std::sync::LazyLock;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use md5::{Digest, Md5};
 
 use crate::{
     config::OmajinaiConfig,
@@ -42,6 +43,55 @@ pub async fn api_get_beatmaps(
     Ok(Some(data))
 }
 
+/// asks the beatmap service to put the .osu file in place on its side.
+pub async fn api_ensure_osu_file(config: &OmajinaiConfig, beatmap: &Beatmap) -> Result<bool> {
+    let url = format!(
+        "{}/v1/ensure-osu/{}?md5={}",
+        config.beatmap_service_url, beatmap.id, beatmap.md5
+    );
+    let resp = CLIENT.get(&url).send().await?;
+
+    Ok(resp.status().is_success())
+}
+
+/// only `load_osu_file` should hit this, so the file gets cached.
+async fn api_get_osu_file(config: &OmajinaiConfig, beatmap: &Beatmap) -> Result<Option<String>> {
+    let url = format!(
+        "{}/v1/get-osu/{}?md5={}",
+        config.beatmap_service_url, beatmap.id, beatmap.md5
+    );
+    let resp = CLIENT.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Ok(None);
+    }
+
+    Ok(Some(resp.text().await?))
+}
+
+/// the .osu file from omajinai's beatmap path, fetched from the beatmap service
+/// when it's missing or outdated.
+pub async fn load_osu_file(config: &OmajinaiConfig, beatmap: &Beatmap) -> Result<Vec<u8>> {
+    let path = config.beatmap_path.join(format!("{}.osu", beatmap.id));
+
+    if let Ok(data) = tokio::fs::read(&path).await
+        && format!("{:x}", Md5::digest(&data)) == beatmap.md5
+    {
+        return Ok(data);
+    }
+
+    let data = api_get_osu_file(config, beatmap)
+        .await?
+        .ok_or_else(|| anyhow!("no .osu file for beatmap {}", beatmap.id))?;
+
+    if let Err(e) = tokio::fs::create_dir_all(&config.beatmap_path).await {
+        tracing::warn!("failed to create {}: {e}", config.beatmap_path.display());
+    } else if let Err(e) = tokio::fs::write(&path, &data).await {
+        tracing::warn!("failed to cache {}: {e}", path.display());
+    }
+
+    Ok(data.into_bytes())
+}
+
 pub fn parse_beatmap_from_api(data: BeatmapApiResponse) -> Beatmap {
     let id = data.id.parse().unwrap_or(0);
     let set_id = data.set_id.parse().unwrap_or(0);
@@ -59,36 +59,45 @@ struct Wrapper {
     data: PerformanceResult,
 }
 
+impl PerformanceRequest {
+    /// applies our own rules on top of the request, every calculator has to go through this.
+    pub fn normalized(&self) -> Self {
+        let mut performance_request = self.clone();
+        let mut mods = Mods::from_bits_truncate(performance_request.mods);
+
+        // lowkey this look stupid
+        if (performance_request.mode == GameMode::CHEAT_OSU.as_i32()
+            || performance_request.mode == GameMode::CHEAT_CHEAT_OSU.as_i32())
+            && mods.contains(Mods::RELAX)
+        {
+            // NOTE: on the client, it has 2 relaxes. relax mod and relax "cheat".
+            //       we should not calculate relax mods on that client because its nerf was deemed "too harsh"
+            //       and because its a cheating stuff, and we know how the people that plays it reeaallly wants
+            mods.remove(Mods::RELAX);
+        }
+
+        if performance_request.mode == GameMode::CHEAT_OSU.as_i32()
+            || performance_request.mode == GameMode::CHEAT_CHEAT_OSU.as_i32()
+        {
+            // since streams are too stupid, we'll use "relax" nerfs to combat that
+            mods.insert(Mods::RELAX);
+        }
+
+        performance_request.mods = mods.bits();
+        // mode as vanilla
+        performance_request.mode %= 4;
+
+        performance_request
+    }
+}
+
 pub async fn calculate_pp(
     config: &OmajinaiConfig,
     requests: &PerformanceRequest,
 ) -> Result<PerformanceResult> {
     let url = format!("{}/calculate", config.base_url);
 
-    let mut performance_request = requests.clone();
-    let mut mods = Mods::from_bits_truncate(performance_request.mods);
-
-    // lowkey this look stupid
-    if (performance_request.mode == GameMode::CHEAT_OSU.as_i32()
-        || performance_request.mode == GameMode::CHEAT_CHEAT_OSU.as_i32())
-        && mods.contains(Mods::RELAX)
-    {
-        // NOTE: on the client, it has 2 relaxes. relax mod and relax "cheat".
-        //       we should not calculate relax mods on that client because its nerf was deemed "too harsh"
-        //       and because its a cheating stuff, and we know how the people that plays it reeaallly wants
-        mods.remove(Mods::RELAX);
-    }
-
-    if performance_request.mode == GameMode::CHEAT_OSU.as_i32()
-        || performance_request.mode == GameMode::CHEAT_CHEAT_OSU.as_i32()
-    {
-        // since streams are too stupid, we'll use "relax" nerfs to combat that
-        mods.insert(Mods::RELAX);
-    }
-
-    performance_request.mods = mods.bits();
-    // mode as vanilla
-    performance_request.mode %= 4;
+    let performance_request = requests.normalized();
 
     let resp = CLIENT.get(&url).query(&performance_request).send().await?;
 
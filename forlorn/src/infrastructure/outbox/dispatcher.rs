@@ -0,0 +1,105 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use dogstatsd::Client as DatadogClient;
+
+use super::Outbox;
+use crate::{config::OutboxConfig, models::OutboxEvent};
+
+const BATCH_SIZE: u32 = 100;
+/// how long a claimed event is reserved for this instance.
+const CLAIM_SECS: u32 = 30;
+
+const BASE_BACKOFF_MS: i64 = 500;
+const MAX_BACKOFF_MS: i64 = 5 * 60 * 1000;
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// publishes the outbox events until the process dies.
+pub async fn run(outbox: Outbox, config: OutboxConfig, metrics: Arc<DatadogClient>) {
+    let claimer = format!(
+        "{}-{}",
+        std::env::var("HOSTNAME").unwrap_or_else(|_| "forlorn".into()),
+        std::process::id()
+    );
+
+    let poll_interval = Duration::from_millis(config.poll_interval_ms);
+    let mut last_prune = Instant::now();
+
+    loop {
+        let events = match outbox.claim_due(&claimer, BATCH_SIZE, CLAIM_SECS).await {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::error!("failed to claim outbox events: {e}");
+                Vec::new()
+            },
+        };
+
+        for event in &events {
+            deliver(&outbox, &config, &metrics, event).await;
+        }
+
+        if last_prune.elapsed() >= PRUNE_INTERVAL {
+            last_prune = Instant::now();
+
+            let older_than = Utc::now() - chrono::Duration::days(config.retention_days);
+            if let Err(e) = outbox.prune_delivered(older_than).await {
+                tracing::warn!("failed to prune delivered outbox events: {e}");
+            }
+        }
+
+        // a full batch means there's probably more waiting
+        if (events.len() as u32) < BATCH_SIZE {
+            outbox.wait(poll_interval).await;
+        }
+    }
+}
+
+async fn deliver(
+    outbox: &Outbox,
+    config: &OutboxConfig,
+    metrics: &DatadogClient,
+    event: &OutboxEvent,
+) {
+    let result = match outbox.publish(event).await {
+        Ok(()) => {
+            let _ = metrics.incr("outbox.delivered", [format!("channel:{}", event.channel)]);
+
+            outbox.mark_delivered(event.id).await
+        },
+        Err(e) => {
+            let attempts = event.attempts + 1;
+
+            let next_attempt_at = (attempts < config.max_attempts).then(|| {
+                let backoff = BASE_BACKOFF_MS
+                    .saturating_mul(1 << attempts.min(20))
+                    .min(MAX_BACKOFF_MS);
+
+                Utc::now() + chrono::Duration::milliseconds(backoff)
+            });
+
+            if next_attempt_at.is_some() {
+                let _ = metrics.incr("outbox.retry", [format!("channel:{}", event.channel)]);
+            } else {
+                let _ = metrics.incr("outbox.failed", [format!("channel:{}", event.channel)]);
+
+                tracing::error!(
+                    "giving up on outbox event {} ({}) after {attempts} attempts: {e}",
+                    event.id,
+                    event.channel,
+                );
+            }
+
+            outbox
+                .mark_attempt_failed(event.id, &e.to_string(), next_attempt_at)
+                .await
+        },
+    };
+
+    if let Err(e) = result {
+        tracing::error!("failed to record outbox event {}: {e}", event.id);
+    }
+}
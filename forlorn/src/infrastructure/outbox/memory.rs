@@ -0,0 +1,128 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::Notify;
+
+use crate::{constants::OutboxStatus, models::OutboxEvent};
+
+/// stand-in for the database & redis.
+///
+/// published events are only marked as delivered, and `fail_publish`
+/// makes every publish fail so the retries can be exercised.
+#[derive(Default)]
+pub struct MemoryOutbox {
+    events: Mutex<Vec<OutboxEvent>>,
+    next_id: AtomicU64,
+    pub fail_publish: AtomicBool,
+    pub(super) wake: Notify,
+}
+
+impl MemoryOutbox {
+    pub(super) fn enqueue(&self, channel: &str, payload: &str) {
+        let now = Utc::now();
+
+        self.events.lock().unwrap().push(OutboxEvent {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            channel: channel.to_string(),
+            payload: payload.to_string(),
+            status: OutboxStatus::Pending.as_i8(),
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: now,
+            created_at: now,
+            delivered_at: None,
+        });
+
+        self.wake.notify_one();
+    }
+
+    pub(super) fn claim_due(&self, limit: u32) -> Vec<OutboxEvent> {
+        let now = Utc::now();
+
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.status == OutboxStatus::Pending.as_i8() && e.next_attempt_at <= now)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn publish(&self, event: &OutboxEvent) -> bool {
+        tracing::debug!("[memory outbox] {} {}", event.channel, event.payload);
+
+        !self.fail_publish.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn mark_delivered(&self, id: u64) {
+        self.update(id, |event| {
+            event.status = OutboxStatus::Delivered.as_i8();
+            event.attempts += 1;
+            event.last_error = None;
+            event.delivered_at = Some(Utc::now());
+        });
+    }
+
+    pub(super) fn mark_attempt_failed(
+        &self,
+        id: u64,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) {
+        self.update(id, |event| {
+            event.attempts += 1;
+            event.last_error = Some(error.to_string());
+
+            match next_attempt_at {
+                Some(at) => event.next_attempt_at = at,
+                None => event.status = OutboxStatus::Failed.as_i8(),
+            }
+        });
+    }
+
+    pub(super) fn fetch_by_status(&self, status: OutboxStatus, limit: u32) -> Vec<OutboxEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|e| e.status == status.as_i8())
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn requeue_failed(&self, id: Option<u64>) -> u64 {
+        let mut requeued = 0;
+
+        for event in self.events.lock().unwrap().iter_mut() {
+            if event.status == OutboxStatus::Failed.as_i8() && id.is_none_or(|id| id == event.id) {
+                event.status = OutboxStatus::Pending.as_i8();
+                event.attempts = 0;
+                event.next_attempt_at = Utc::now();
+                requeued += 1;
+            }
+        }
+
+        requeued
+    }
+
+    pub(super) fn prune_delivered(&self, older_than: DateTime<Utc>) -> u64 {
+        let mut events = self.events.lock().unwrap();
+        let before = events.len();
+
+        events.retain(|e| e.delivered_at.is_none_or(|at| at >= older_than));
+
+        (before - events.len()) as u64
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut OutboxEvent)) {
+        if let Some(event) = self.events.lock().unwrap().iter_mut().find(|e| e.id == id) {
+            f(event);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use tokio::sync::Notify;
+
+use crate::{
+    constants::OutboxStatus,
+    infrastructure::{
+        database::{DbConnection, DbPoolManager},
+        redis::{RedisConnectionManager, publish::publish},
+    },
+    models::OutboxEvent,
+    repository,
+};
+
+pub mod dispatcher;
+pub mod memory;
+
+pub use memory::MemoryOutbox;
+
+/// every pubsub event goes through here first, so a redis blip doesn't lose it.
+///
+/// events are written to the outbox and `dispatcher::run` publishes them.
+#[derive(Clone)]
+pub enum Outbox {
+    Database {
+        db: DbPoolManager,
+        redis: RedisConnectionManager,
+        wake: Arc<Notify>,
+    },
+    /// keeps everything in memory and never touches redis,
+    /// for tests and running locally.
+    Memory(Arc<MemoryOutbox>),
+}
+
+/// where an event gets written to. either the outbox on its own, or a
+/// transaction so the event only goes out if whatever caused it commits.
+pub trait EventSink {
+    fn enqueue(&mut self, channel: &str, payload: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl EventSink for &Outbox {
+    async fn enqueue(&mut self, channel: &str, payload: &str) -> Result<()> {
+        Outbox::enqueue(self, channel, payload).await
+    }
+}
+
+impl EventSink for &MemoryOutbox {
+    async fn enqueue(&mut self, channel: &str, payload: &str) -> Result<()> {
+        MemoryOutbox::enqueue(self, channel, payload);
+
+        Ok(())
+    }
+}
+
+/// the dispatcher only sees it once the transaction commits,
+/// call `Outbox::wake` after that so it doesn't wait for the next poll.
+/// only the database backend reads these back.
+impl EventSink for &mut DbConnection {
+    async fn enqueue(&mut self, channel: &str, payload: &str) -> Result<()> {
+        repository::outbox::insert(self, channel, payload).await?;
+
+        Ok(())
+    }
+}
+
+impl Outbox {
+    pub fn database(db: DbPoolManager, redis: RedisConnectionManager) -> Self {
+        Outbox::Database { db, redis, wake: Arc::new(Notify::new()) }
+    }
+
+    pub fn memory() -> Self {
+        Outbox::Memory(Arc::new(MemoryOutbox::default()))
+    }
+
+    pub async fn enqueue(&self, channel: &str, payload: &str) -> Result<()> {
+        match self {
+            Outbox::Database { db, redis, wake } => {
+                let inserted = match db.acquire().await {
+                    Ok(mut conn) => repository::outbox::insert(&mut conn, channel, payload).await,
+                    Err(e) => Err(e.into()),
+                };
+
+                match inserted {
+                    Ok(_) => wake.notify_one(),
+                    Err(e) => {
+                        // publishing it right away is still better than losing it
+                        tracing::error!("failed to write {channel} to the outbox: {e}");
+                        publish(redis, channel, payload).await?;
+                    },
+                }
+            },
+            Outbox::Memory(memory) => memory.enqueue(channel, payload),
+        }
+
+        Ok(())
+    }
+
+    pub async fn claim_due(
+        &self,
+        claimer: &str,
+        limit: u32,
+        claim_secs: u32,
+    ) -> Result<Vec<OutboxEvent>> {
+        match self {
+            Outbox::Database { db, .. } => {
+                repository::outbox::claim_due(db, claimer, limit, claim_secs).await
+            },
+            Outbox::Memory(memory) => Ok(memory.claim_due(limit)),
+        }
+    }
+
+    pub async fn publish(&self, event: &OutboxEvent) -> Result<()> {
+        match self {
+            Outbox::Database { redis, .. } => {
+                publish(redis, &event.channel, &event.payload).await?;
+                Ok(())
+            },
+            Outbox::Memory(memory) => memory
+                .publish(event)
+                .then_some(())
+                .ok_or_else(|| anyhow!("memory outbox is set to fail")),
+        }
+    }
+
+    pub async fn mark_delivered(&self, id: u64) -> Result<()> {
+        match self {
+            Outbox::Database { db, .. } => repository::outbox::mark_delivered(db, id).await,
+            Outbox::Memory(memory) => {
+                memory.mark_delivered(id);
+                Ok(())
+            },
+        }
+    }
+
+    /// `next_attempt_at` being none means we gave up on it.
+    pub async fn mark_attempt_failed(
+        &self,
+        id: u64,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        match self {
+            Outbox::Database { db, .. } => {
+                repository::outbox::mark_attempt_failed(db, id, error, next_attempt_at).await
+            },
+            Outbox::Memory(memory) => {
+                memory.mark_attempt_failed(id, error, next_attempt_at);
+                Ok(())
+            },
+        }
+    }
+
+    pub async fn fetch_by_status(
+        &self,
+        status: OutboxStatus,
+        limit: u32,
+    ) -> Result<Vec<OutboxEvent>> {
+        match self {
+            Outbox::Database { db, .. } => {
+                repository::outbox::fetch_by_status(db, status, limit).await
+            },
+            Outbox::Memory(memory) => Ok(memory.fetch_by_status(status, limit)),
+        }
+    }
+
+    /// puts failed events back in the queue, every one of them if `id` is none.
+    pub async fn requeue_failed(&self, id: Option<u64>) -> Result<u64> {
+        let requeued = match self {
+            Outbox::Database { db, .. } => repository::outbox::requeue_failed(db, id).await?,
+            Outbox::Memory(memory) => memory.requeue_failed(id),
+        };
+
+        self.wake();
+
+        Ok(requeued)
+    }
+
+    pub async fn prune_delivered(&self, older_than: DateTime<Utc>) -> Result<u64> {
+        match self {
+            Outbox::Database { db, .. } => {
+                repository::outbox::prune_delivered(db, older_than).await
+            },
+            Outbox::Memory(memory) => Ok(memory.prune_delivered(older_than)),
+        }
+    }
+
+    /// waits until something gets enqueued, or `timeout` passes.
+    pub async fn wait(&self, timeout: Duration) {
+        let wake = match self {
+            Outbox::Database { wake, .. } => wake,
+            Outbox::Memory(memory) => &memory.wake,
+        };
+
+        let _ = tokio::time::timeout(timeout, wake.notified()).await;
+    }
+
+    pub fn wake(&self) {
+        match self {
+            Outbox::Database { wake, .. } => wake.notify_one(),
+            Outbox::Memory(memory) => memory.wake.notify_one(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use rosu_pp::model::mode::GameMode as RosuGameMode;
+
+use crate::{
+    config::OmajinaiConfig,
+    infrastructure::omajinai::{PerformanceRequest, PerformanceResult, beatmap::load_osu_file},
+    models::Beatmap,
+};
+
+/// calculates with rosu-pp, so we don't depend on omajinai being up.
+///
+/// reads the .osu files omajinai keeps in its beatmap path, and only asks
+/// the beatmap service when the file is missing or outdated.
+#[derive(Clone)]
+pub struct LocalCalculator {
+    config: OmajinaiConfig,
+}
+
+impl LocalCalculator {
+    pub fn new(config: OmajinaiConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        request: &PerformanceRequest,
+    ) -> Result<PerformanceResult> {
+        let osu_file = load_osu_file(&self.config, beatmap).await?;
+        let request = request.normalized();
+
+        tokio::task::spawn_blocking(move || calculate_blocking(&osu_file, &request)).await?
+    }
+}
+
+/// expects an already normalized request.
+fn calculate_blocking(osu_file: &[u8], request: &PerformanceRequest) -> Result<PerformanceResult> {
+    let map = rosu_pp::Beatmap::from_bytes(osu_file)?;
+
+    let mode = match request.mode {
+        1 => RosuGameMode::Taiko,
+        2 => RosuGameMode::Catch,
+        3 => RosuGameMode::Mania,
+        _ => RosuGameMode::Osu,
+    };
+
+    let performance = |n300: Option<i32>, misses: i32, combo: Option<u32>| {
+        let mut calculator = rosu_pp::Performance::new(&map)
+            .mode_or_ignore(mode)
+            .mods(request.mods as u32)
+            .lazer(false)
+            .misses(misses.max(0) as u32);
+
+        // -1 means the client didn't send one
+        if request.clock_rate > 0.0 {
+            calculator = calculator.clock_rate(request.clock_rate);
+        }
+
+        if let Some(combo) = combo {
+            calculator = calculator.combo(combo);
+        }
+
+        match n300 {
+            Some(n300) => calculator
+                .n300(n300.max(0) as u32)
+                .n100(request.n100.unwrap_or(0).max(0) as u32)
+                .n50(request.n50.unwrap_or(0).max(0) as u32)
+                .n_geki(request.ngeki.unwrap_or(0).max(0) as u32)
+                .n_katu(request.nkatu.unwrap_or(0).max(0) as u32),
+            None => calculator.accuracy(request.accuracy as f64),
+        }
+        .calculate()
+    };
+
+    let combo = (request.max_combo > 0).then_some(request.max_combo as u32);
+    let attributes = performance(request.n300, request.miss_count, combo);
+
+    // same play with the misses turned into 300s and a full combo
+    let hypothetical = performance(
+        request.n300.map(|n300| n300 + request.miss_count.max(0)),
+        0,
+        Some(attributes.max_combo()),
+    );
+
+    Ok(PerformanceResult {
+        stars: attributes.stars() as f32,
+        pp: attributes.pp() as f32,
+        hypothetical_pp: hypothetical.pp() as f32,
+    })
+}
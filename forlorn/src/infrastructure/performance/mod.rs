@@ -0,0 +1,92 @@
+use anyhow::Result;
+
+use crate::{
+    config::{Config, OmajinaiConfig},
+    infrastructure::omajinai::{PerformanceRequest, PerformanceResult, calculate_pp},
+    models::Beatmap,
+};
+
+pub mod local;
+
+pub use local::LocalCalculator;
+
+/// something that can turn a play into pp & stars.
+#[derive(Clone)]
+pub enum PerformanceCalculator {
+    /// the omajinai sidecar, over http.
+    Omajinai(OmajinaiConfig),
+    /// in-process, from the cached .osu file.
+    Local(LocalCalculator),
+}
+
+impl PerformanceCalculator {
+    /// "omajinai" or "local", anything else is none.
+    pub fn from_name(name: &str, config: &Config) -> Option<Self> {
+        match name {
+            "omajinai" => Some(Self::Omajinai(config.omajinai.clone())),
+            "local" => Some(Self::Local(LocalCalculator::new(config.omajinai.clone()))),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Omajinai(_) => "omajinai",
+            Self::Local(_) => "local",
+        }
+    }
+
+    pub async fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        request: &PerformanceRequest,
+    ) -> Result<PerformanceResult> {
+        match self {
+            Self::Omajinai(config) => calculate_pp(config, request).await,
+            Self::Local(calculator) => calculator.calculate(beatmap, request).await,
+        }
+    }
+}
+
+/// the calculator we ask first, and the one we ask when that fails.
+#[derive(Clone)]
+pub struct Performance {
+    primary: PerformanceCalculator,
+    fallback: Option<PerformanceCalculator>,
+}
+
+impl Performance {
+    pub fn from_config(config: &Config) -> Self {
+        let primary = PerformanceCalculator::from_name(&config.performance.primary, config)
+            .unwrap_or_else(|| PerformanceCalculator::Omajinai(config.omajinai.clone()));
+
+        let fallback = PerformanceCalculator::from_name(&config.performance.fallback, config)
+            .filter(|fallback| fallback.name() != primary.name());
+
+        Self { primary, fallback }
+    }
+
+    pub async fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        request: &PerformanceRequest,
+    ) -> Result<PerformanceResult> {
+        let err = match self.primary.calculate(beatmap, request).await {
+            Ok(result) => return Ok(result),
+            Err(e) => e,
+        };
+
+        let Some(fallback) = &self.fallback else {
+            return Err(err);
+        };
+
+        tracing::warn!(
+            "{} pp calculation failed for beatmap {}, falling back to {}: {err}",
+            self.primary.name(),
+            beatmap.id,
+            fallback.name(),
+        );
+
+        fallback.calculate(beatmap, request).await
+    }
+}
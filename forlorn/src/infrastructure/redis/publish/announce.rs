@@ -1,8 +1,7 @@
-use super::publish;
-use crate::infrastructure::redis::RedisConnectionManager;
+use crate::infrastructure::outbox::EventSink;
 
-pub async fn announce(redis: &RedisConnectionManager, score: u64) -> anyhow::Result<()> {
-    publish(redis, "refx:announce", &score.to_string()).await?;
+pub async fn announce(mut outbox: impl EventSink, score: u64) -> anyhow::Result<()> {
+    outbox.enqueue("refx:announce", &score.to_string()).await?;
 
     Ok(())
 }
@@ -0,0 +1,11 @@
+use crate::{
+    infrastructure::redis::{RedisConnectionManager, publish::publish},
+    repository::beatmap_cache::Eviction,
+};
+
+/// the caches don't outlive a restart either, so this skips the outbox.
+pub async fn evict_map(redis: &RedisConnectionManager, eviction: &Eviction) -> anyhow::Result<()> {
+    publish(redis, "forlorn:evict_map", &eviction.to_string()).await?;
+
+    Ok(())
+}
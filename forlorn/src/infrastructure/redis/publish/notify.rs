@@ -1,18 +1,13 @@
-use super::publish;
-use crate::infrastructure::redis::RedisConnectionManager;
+use crate::infrastructure::outbox::EventSink;
 
 /// sent packet 24 not 25
-pub async fn notify(
-    redis: &RedisConnectionManager,
-    userid: i32,
-    message: &str,
-) -> anyhow::Result<()> {
-    publish(
-        redis,
-        "refx:notify",
-        &format!("{}|{}", &userid.to_string(), message),
-    )
-    .await?;
+pub async fn notify(mut outbox: impl EventSink, userid: i32, message: &str) -> anyhow::Result<()> {
+    outbox
+        .enqueue(
+            "refx:notify",
+            &format!("{}|{}", &userid.to_string(), message),
+        )
+        .await?;
 
     Ok(())
 }
@@ -1,8 +1,9 @@
-use super::publish;
-use crate::infrastructure::redis::RedisConnectionManager;
+use crate::infrastructure::outbox::EventSink;
 
-pub async fn refresh_stats(redis: &RedisConnectionManager, userid: i32) -> anyhow::Result<()> {
-    publish(redis, "refx:refresh_stats", &userid.to_string()).await?;
+pub async fn refresh_stats(mut outbox: impl EventSink, userid: i32) -> anyhow::Result<()> {
+    outbox
+        .enqueue("refx:refresh_stats", &userid.to_string())
+        .await?;
 
     Ok(())
 }
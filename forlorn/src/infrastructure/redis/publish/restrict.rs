@@ -1,19 +1,19 @@
-use super::publish;
-use crate::infrastructure::redis::RedisConnectionManager;
+use crate::infrastructure::outbox::EventSink;
 
-pub async fn restrict(
-    redis: &RedisConnectionManager,
-    userid: i32,
-    reason: &str,
-) -> anyhow::Result<()> {
+pub async fn restrict(mut outbox: impl EventSink, userid: i32, reason: &str) -> anyhow::Result<()> {
     tracing::warn!("Restricted user id {userid} for {reason}");
 
-    publish(
-        redis,
-        "refx:restrict",
-        &format!("{}|{}", &userid.to_string(), reason),
-    )
-    .await?;
+    outbox
+        .enqueue(
+            "refx:restrict",
+            &format!("{}|{}", &userid.to_string(), reason),
+        )
+        .await?;
+
+    // every replica drops them from its cached boards
+    outbox
+        .enqueue("forlorn:user_restricted", &userid.to_string())
+        .await?;
 
     Ok(())
 }
@@ -1,8 +1,9 @@
-use super::publish;
-use crate::infrastructure::redis::RedisConnectionManager;
+use crate::infrastructure::outbox::EventSink;
 
-pub async fn score_submitted(redis: &RedisConnectionManager, score_id: u64) -> anyhow::Result<()> {
-    publish(redis, "refx:score_submitted", &score_id.to_string()).await?;
+pub async fn score_submitted(mut outbox: impl EventSink, score_id: u64) -> anyhow::Result<()> {
+    outbox
+        .enqueue("refx:score_submitted", &score_id.to_string())
+        .await?;
 
     Ok(())
 }
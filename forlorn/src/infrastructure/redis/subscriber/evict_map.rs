@@ -0,0 +1,16 @@
+use anyhow::{Result, anyhow};
+
+use crate::repository::{beatmap::BEATMAP_CACHE, beatmap_cache::Eviction};
+
+/// a replica dropped maps from its cache, ours follows. that includes
+/// our own broadcasts, at worst that costs a reread.
+pub async fn evict_map(payload: &str) -> Result<()> {
+    let eviction =
+        Eviction::parse(payload.trim()).ok_or_else(|| anyhow!("invalid eviction {payload}"))?;
+
+    let removed = BEATMAP_CACHE.apply(&eviction);
+
+    tracing::debug!("{removed} beatmaps evicted for {eviction}");
+
+    Ok(())
+}
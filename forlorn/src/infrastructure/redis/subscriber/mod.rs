@@ -3,9 +3,11 @@ use tokio_stream::StreamExt;
 
 use crate::state::AppState;
 
+mod evict_map;
 mod refresh_map;
+mod user_restricted;
 
-const CHANNELS: &[&str] = &["forlorn:refresh_map"];
+const CHANNELS: &[&str] = &["forlorn:refresh_map", "forlorn:user_restricted", "forlorn:evict_map"];
 
 pub struct SubscriberHandler {
     state: AppState,
@@ -33,7 +35,13 @@ impl SubscriberHandler {
 
             tokio::spawn(async move {
                 let result = match channel.as_str() {
-                    "forlorn:refresh_map" => refresh_map::refresh_map(&state.db, &payload).await,
+                    "forlorn:refresh_map" => {
+                        refresh_map::refresh_map(&state.db, &state.redis, &payload).await
+                    },
+                    "forlorn:user_restricted" => {
+                        user_restricted::user_restricted(&state.db, &state.redis, &payload).await
+                    },
+                    "forlorn:evict_map" => evict_map::evict_map(&payload).await,
 
                     _ => Ok(()),
                 };
@@ -1,16 +1,27 @@
 use anyhow::Result;
 
-use crate::{infrastructure::database::DbPoolManager, repository};
+use crate::{
+    infrastructure::{database::DbPoolManager, redis::RedisConnectionManager},
+    repository,
+    usecases::leaderboard::invalidate_map,
+};
 
-pub async fn refresh_map(db: &DbPoolManager, md5: &str) -> Result<()> {
-    {
-        let mut cache = repository::beatmap::BEATMAP_CACHE.write().await;
-        cache.remove(md5);
-    }
+/// every replica gets this one, so the cache is only dropped locally.
+pub async fn refresh_map(
+    db: &DbPoolManager,
+    redis: &RedisConnectionManager,
+    md5: &str,
+) -> Result<()> {
+    repository::beatmap::BEATMAP_CACHE.remove_md5(md5);
+
+    // the status might have changed, so the cached boards go too
+    invalidate_map(redis, md5).await;
+
+    // it's on our side now, or at least worth another look
+    repository::missing_map::clear(redis, md5).await?;
 
     if let Some(bmap) = repository::beatmap::md5_from_database(db, md5).await? {
-        let mut cache = repository::beatmap::BEATMAP_CACHE.write().await;
-        cache.insert(md5.to_string(), bmap);
+        repository::beatmap::BEATMAP_CACHE.insert(bmap);
 
         tracing::info!("beatmap {} refreshed!", md5);
     }
@@ -0,0 +1,26 @@
+use anyhow::Result;
+
+use crate::{
+    infrastructure::{database::DbPoolManager, redis::RedisConnectionManager},
+    usecases::leaderboard::invalidate_user,
+};
+
+/// published once a restriction went through, their scores shouldn't
+/// be served from the cached boards anymore.
+///
+/// `publish::restrict` sends it for the restrictions made here. bancho should
+/// publish it too (payload is just the user id) once it applied any restriction,
+/// that also covers the ones from the panel and a board rebuilt in between.
+pub async fn user_restricted(
+    db: &DbPoolManager,
+    redis: &RedisConnectionManager,
+    payload: &str,
+) -> Result<()> {
+    let user_id: i32 = payload.trim().parse()?;
+
+    invalidate_user(db, redis, user_id).await?;
+
+    tracing::info!("leaderboards of restricted user {user_id} invalidated!");
+
+    Ok(())
+}
@@ -4,6 +4,8 @@ pub mod dto;
 pub mod geoloc;
 pub mod infrastructure;
 pub mod models;
+pub mod osu_file;
+pub mod replay;
 pub mod repository;
 pub mod routes;
 pub mod state;
@@ -1,27 +1,23 @@
-mod config;
-mod constants;
-mod dto;
-mod geoloc;
-mod infrastructure;
-mod models;
-mod repository;
-mod routes;
-mod state;
-mod usecases;
-mod utils;
-
 use std::sync::Arc;
 
 use anyhow::Result;
-use config::Config;
 use dotenvy::dotenv;
-use infrastructure::{SubscriberHandler, database, datadog, redis};
-use routes::create_routes;
-use state::AppState;
+use forlorn::{
+    config::Config,
+    infrastructure::{
+        SubscriberHandler, database, datadog,
+        outbox::{self, Outbox},
+        redis,
+    },
+    repository,
+    routes::create_routes,
+    state::AppState,
+    usecases,
+    utils::shutdown_signal,
+};
 use storage::Storage;
 use tokio::net::TcpListener;
 use tracing_subscriber::EnvFilter;
-use utils::shutdown_signal;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -35,6 +31,11 @@ async fn main() -> Result<()> {
         redis::create_connection(&config.redis).await?;
     let metrics = Arc::new(datadog::create_metric(config.datadog.clone()));
 
+    let outbox = match config.outbox.backend.as_str() {
+        "memory" => Outbox::memory(),
+        _ => Outbox::database(db_pool.clone(), redis_conn.clone()),
+    };
+
     let storage = Storage::new(
         config.replay_path.clone(),
         config.screenshot_path.clone(),
@@ -52,6 +53,7 @@ async fn main() -> Result<()> {
         db_pool,
         redis_conn,
         subscriber_conn,
+        outbox.clone(),
         score_locks,
         metrics,
     );
@@ -64,6 +66,23 @@ async fn main() -> Result<()> {
         }
     });
 
+    tokio::spawn(outbox::dispatcher::run(
+        outbox,
+        config.outbox.clone(),
+        state.metrics.clone(),
+    ));
+
+    tokio::spawn(usecases::pp_queue::run(state.clone()));
+
+    repository::beatmap::BEATMAP_CACHE.set_capacity(config.beatmap_cache_size);
+    tokio::spawn(usecases::beatmap::report_cache_metrics(
+        state.metrics.clone(),
+    ));
+    tokio::spawn(usecases::beatmap::broadcast_evictions(
+        state.redis.clone(),
+        repository::beatmap::BEATMAP_CACHE.subscribe_evictions(),
+    ));
+
     let app = create_routes().with_state(state);
 
     let addr = format!("0.0.0.0:{}", config.port);
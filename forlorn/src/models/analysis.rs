@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::ReviewThresholds, models::Score, replay::ReplayStatistics};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ScoreAnalysis {
+    pub score_id: u64,
+    pub mode: i32,
+
+    pub unstable_rate: Option<f32>,
+    pub press_count: i32,
+    pub press_interval_mean: Option<f32>,
+    pub press_interval_stddev: Option<f32>,
+    pub hold_time_mean: Option<f32>,
+    pub hold_time_variance: Option<f32>,
+    pub snap_count: i32,
+
+    pub flagged: bool,
+    pub flag_reasons: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScoreAnalysis {
+    pub fn from_statistics(score: &Score, stats: ReplayStatistics) -> Self {
+        let reasons = review_reasons(&stats, &ReviewThresholds::for_mode(score.mode()));
+
+        Self {
+            score_id: score.id,
+            mode: score.mode,
+            unstable_rate: stats.unstable_rate,
+            press_count: stats.press_count,
+            press_interval_mean: stats.press_interval_mean,
+            press_interval_stddev: stats.press_interval_stddev,
+            hold_time_mean: stats.hold_time_mean,
+            hold_time_variance: stats.hold_time_variance,
+            snap_count: stats.snap_count,
+            flagged: !reasons.is_empty(),
+            flag_reasons: (!reasons.is_empty()).then(|| reasons.join(", ")),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+fn review_reasons(stats: &ReplayStatistics, thresholds: &ReviewThresholds) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    if let (Some(ur), Some(min)) = (stats.unstable_rate, thresholds.min_unstable_rate)
+        && ur < min
+    {
+        reasons.push(format!("ur {ur:.2} < {min}"));
+    }
+
+    if let (Some(variance), Some(min)) =
+        (stats.hold_time_variance, thresholds.min_hold_time_variance)
+        && variance < min
+    {
+        reasons.push(format!("hold time variance {variance:.2} < {min}"));
+    }
+
+    if let (Some(interval), Some(min)) = (stats.press_interval_mean, thresholds.min_press_interval)
+        && interval < min
+    {
+        reasons.push(format!("press interval {interval:.2}ms < {min}ms"));
+    }
+
+    if let Some(max) = thresholds.max_snaps
+        && stats.snap_count > max
+    {
+        reasons.push(format!("{} snaps > {max}", stats.snap_count));
+    }
+
+    reasons
+}
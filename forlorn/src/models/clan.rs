@@ -9,3 +9,27 @@ pub struct Clan {
     pub owner: i32,
     pub created_at: DateTime<Utc>,
 }
+
+/// a clan's combined best scores on a map.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ClanMapRanking {
+    pub clan_id: i32,
+    pub name: String,
+    pub tag: String,
+    pub members: i64,
+    pub pp: f64,
+    pub score: i64,
+}
+
+/// a clan's combined stats in one mode.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ClanModeStats {
+    pub mode: i8,
+    pub members: i64,
+    pub pp: i64,
+    pub rscore: i64,
+    pub tscore: i64,
+    pub plays: i64,
+    pub playtime: i64,
+    pub acc: f64,
+}
@@ -10,6 +10,9 @@ use crate::{
 pub struct LeaderboardScore {
     pub id: u64,
     pub preferred_metric: f64,
+    pub pp: f32,
+    pub acc: f32,
+    pub grade: String,
     pub max_combo: i32,
     pub n50: i32,
     pub n100: i32,
@@ -22,6 +25,7 @@ pub struct LeaderboardScore {
     pub play_time: i64,
     pub userid: i32,
     pub name: String,
+    pub clan_tag: Option<String>,
 
     pub aim_assist_type: Option<i8>,
     pub maple_values: Option<Json<MapleAimAssistValues>>,
@@ -46,6 +50,13 @@ pub struct LeaderboardScore {
 }
 
 impl LeaderboardScore {
+    pub fn display_name(&self) -> String {
+        match &self.clan_tag {
+            Some(tag) => format!("[{tag}] {}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
     pub fn aim_assist_type(&self) -> AimAssistType {
         self.aim_assist_type
             .map(AimAssistType::from_i8)
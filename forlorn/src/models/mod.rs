@@ -1,19 +1,27 @@
 pub mod achievement;
+pub mod analysis;
 pub mod beatmap;
 pub mod clan;
 pub mod error;
 pub mod favourite;
 pub mod leaderboard;
+pub mod outbox;
+pub mod replay_view;
 pub mod score;
 pub mod stats;
+pub mod tournament;
 pub mod user;
 
 pub use achievement::{Achievement, Condition};
+pub use analysis::ScoreAnalysis;
 pub use beatmap::{Beatmap, BeatmapApiResponse, BeatmapChild, BeatmapSet, BeatmapSetInfo};
-pub use clan::Clan;
+pub use clan::{Clan, ClanMapRanking, ClanModeStats};
 pub use error::ClientError;
 pub use favourite::Favourites;
 pub use leaderboard::{LeaderboardScore, PersonalBest};
+pub use outbox::OutboxEvent;
+pub use replay_view::WatchedReplay;
 pub use score::{AimAssistType, MapleAimAssistValues, Score};
 pub use stats::Stats;
+pub use tournament::{Tournament, TournamentStanding};
 pub use user::User;
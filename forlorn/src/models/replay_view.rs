@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WatchedReplay {
+    pub score_id: u64,
+    pub views: u32,
+
+    pub userid: i32,
+    pub name: String,
+    pub map_md5: String,
+    pub mods: i32,
+    pub pp: f32,
+    pub acc: f32,
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, types::Json};
 
 use super::user::User;
-use crate::constants::{GameMode, Grade, Mods, SubmissionStatus};
+use crate::constants::{GameMode, Grade, LAZER_ONLINE_CHECKSUM, Mods, SubmissionStatus};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Score {
@@ -160,6 +160,11 @@ impl Score {
         SubmissionStatus::from_i32(self.status)
     }
 
+    /// lazer scores keep their own best per map, apart from the stable one.
+    pub fn is_lazer(&self) -> bool {
+        self.online_checksum == LAZER_ONLINE_CHECKSUM
+    }
+
     pub fn get_ach_stat(&self, name: &str) -> f64 {
         match name {
             "accuracy" => self.acc as f64,
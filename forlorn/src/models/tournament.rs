@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Tournament {
+    pub id: u32,
+    pub name: String,
+    pub mods: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// a player's best scores across the pool, added up.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TournamentStanding {
+    pub userid: i32,
+    pub name: String,
+    pub maps_played: i64,
+    pub pp: f64,
+    pub score: i64,
+}
@@ -24,6 +24,12 @@ pub struct User {
     pub api_key: Option<String>,
     pub whitelist: i32,
     pub preferred_metric: String,
+    /// only the player & staff can watch their replays.
+    pub private_replays: bool,
+    /// the country tab shows their clan instead.
+    pub clan_leaderboard: bool,
+    /// the global tab shows the tournament the map is in, if there's one running.
+    pub tournament_leaderboard: bool,
 }
 
 impl User {
@@ -39,6 +45,10 @@ impl User {
         !Privileges::from_bits_retain(self.privilege).contains(Privileges::UNRESTRICTED)
     }
 
+    pub fn staff(&self) -> bool {
+        Privileges::from_bits_retain(self.privilege).intersects(Privileges::STAFF)
+    }
+
     pub fn whitelist_stage(&self) -> usize {
         if self.privilege & Privileges::WHITELISTED.bits() != 0 {
             self.whitelist.clamp(1, 4) as usize
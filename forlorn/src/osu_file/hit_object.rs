@@ -0,0 +1,87 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitObjectKind {
+    Circle,
+    Slider {
+        /// 1 + the amount of repeats.
+        slides: u32,
+        /// in osu!pixels.
+        length: f64,
+    },
+    Spinner {
+        end_time: f64,
+    },
+    /// mania only.
+    Hold {
+        end_time: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HitObject {
+    pub x: f32,
+    pub y: f32,
+    pub time: f64,
+    pub new_combo: bool,
+    pub kind: HitObjectKind,
+}
+
+const CIRCLE: u32 = 1 << 0;
+const SLIDER: u32 = 1 << 1;
+const NEW_COMBO: u32 = 1 << 2;
+const SPINNER: u32 = 1 << 3;
+const HOLD: u32 = 1 << 7;
+
+impl HitObject {
+    /// `x,y,time,type,hitSound,objectParams,hitSample`
+    pub fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+
+        let x = parts.first()?.parse().ok()?;
+        let y = parts.get(1)?.parse().ok()?;
+        let time = parts.get(2)?.parse().ok()?;
+        let kind_bits: u32 = parts.get(3)?.parse().ok()?;
+
+        let kind = if kind_bits & CIRCLE != 0 {
+            HitObjectKind::Circle
+        } else if kind_bits & SLIDER != 0 {
+            // curve points are in parts[5], we only care about the rest
+            HitObjectKind::Slider {
+                slides: parts.get(6)?.parse::<u32>().ok()?.max(1),
+                length: parts.get(7).and_then(|l| l.parse().ok()).unwrap_or(0.0),
+            }
+        } else if kind_bits & SPINNER != 0 {
+            HitObjectKind::Spinner { end_time: parts.get(5)?.parse().ok()? }
+        } else if kind_bits & HOLD != 0 {
+            // end time is glued to the hit sample, `endTime:normalSet:...`
+            HitObjectKind::Hold {
+                end_time: parts.get(5)?.split(':').next()?.parse().ok()?,
+            }
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            x,
+            y,
+            time,
+            new_combo: kind_bits & NEW_COMBO != 0,
+            kind,
+        })
+    }
+
+    pub fn is_circle(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Circle)
+    }
+
+    pub fn is_slider(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Slider { .. })
+    }
+
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Spinner { .. })
+    }
+
+    pub fn is_hold(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Hold { .. })
+    }
+}
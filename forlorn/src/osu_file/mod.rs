@@ -0,0 +1,7 @@
+pub mod hit_object;
+pub mod parser;
+pub mod timing;
+
+pub use hit_object::{HitObject, HitObjectKind};
+pub use parser::{Difficulty, General, Metadata, OsuFile};
+pub use timing::TimingPoint;
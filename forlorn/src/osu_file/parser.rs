@@ -0,0 +1,325 @@
+use anyhow::{Result, bail};
+
+use super::{HitObject, HitObjectKind, TimingPoint};
+
+#[derive(Debug, Clone, Default)]
+pub struct General {
+    pub audio_filename: String,
+    pub audio_lead_in: i32,
+    pub preview_time: i32,
+    pub stack_leniency: f32,
+    pub mode: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub title: String,
+    pub title_unicode: String,
+    pub artist: String,
+    pub artist_unicode: String,
+    pub creator: String,
+    pub version: String,
+    pub source: String,
+    pub tags: String,
+    pub beatmap_id: i32,
+    pub beatmap_set_id: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Difficulty {
+    pub hp: f32,
+    pub cs: f32,
+    pub od: f32,
+    pub ar: f32,
+    pub slider_multiplier: f64,
+    pub slider_tick_rate: f64,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            hp: 5.0,
+            cs: 5.0,
+            od: 5.0,
+            ar: 5.0,
+            slider_multiplier: 1.4,
+            slider_tick_rate: 1.0,
+        }
+    }
+}
+
+/// a parsed .osu file, only the parts we actually use.
+#[derive(Debug, Clone, Default)]
+pub struct OsuFile {
+    pub format_version: i32,
+    pub general: General,
+    pub metadata: Metadata,
+    pub difficulty: Difficulty,
+    pub timing_points: Vec<TimingPoint>,
+    pub hit_objects: Vec<HitObject>,
+}
+
+impl OsuFile {
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut lines = data
+            .lines()
+            .map(|line| line.trim_start_matches('\u{feff}').trim());
+
+        let Some(format_version) = lines
+            .by_ref()
+            .find(|line| !line.is_empty())
+            .and_then(|line| line.strip_prefix("osu file format v"))
+            .and_then(|version| version.trim().parse().ok())
+        else {
+            bail!("not an .osu file");
+        };
+
+        let mut osu = Self { format_version, ..Default::default() };
+
+        let mut section = "";
+        let mut ar = None;
+
+        for line in lines {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name;
+                continue;
+            }
+
+            match section {
+                "TimingPoints" => osu.timing_points.extend(TimingPoint::parse(line)),
+                "HitObjects" => osu.hit_objects.extend(HitObject::parse(line)),
+                "General" | "Metadata" | "Difficulty" => {
+                    let Some((key, value)) = line.split_once(':') else {
+                        continue;
+                    };
+
+                    let (key, value) = (key.trim(), value.trim());
+
+                    match (section, key) {
+                        ("General", "AudioFilename") => osu.general.audio_filename = value.into(),
+                        ("General", "AudioLeadIn") => {
+                            osu.general.audio_lead_in = value.parse().unwrap_or_default()
+                        },
+                        ("General", "PreviewTime") => {
+                            osu.general.preview_time = value.parse().unwrap_or_default()
+                        },
+                        ("General", "StackLeniency") => {
+                            osu.general.stack_leniency = value.parse().unwrap_or_default()
+                        },
+                        ("General", "Mode") => osu.general.mode = value.parse().unwrap_or_default(),
+
+                        ("Metadata", "Title") => osu.metadata.title = value.into(),
+                        ("Metadata", "TitleUnicode") => osu.metadata.title_unicode = value.into(),
+                        ("Metadata", "Artist") => osu.metadata.artist = value.into(),
+                        ("Metadata", "ArtistUnicode") => osu.metadata.artist_unicode = value.into(),
+                        ("Metadata", "Creator") => osu.metadata.creator = value.into(),
+                        ("Metadata", "Version") => osu.metadata.version = value.into(),
+                        ("Metadata", "Source") => osu.metadata.source = value.into(),
+                        ("Metadata", "Tags") => osu.metadata.tags = value.into(),
+                        ("Metadata", "BeatmapID") => {
+                            osu.metadata.beatmap_id = value.parse().unwrap_or_default()
+                        },
+                        ("Metadata", "BeatmapSetID") => {
+                            osu.metadata.beatmap_set_id = value.parse().unwrap_or_default()
+                        },
+
+                        ("Difficulty", "HPDrainRate") => {
+                            osu.difficulty.hp = value.parse().unwrap_or(osu.difficulty.hp)
+                        },
+                        ("Difficulty", "CircleSize") => {
+                            osu.difficulty.cs = value.parse().unwrap_or(osu.difficulty.cs)
+                        },
+                        ("Difficulty", "OverallDifficulty") => {
+                            osu.difficulty.od = value.parse().unwrap_or(osu.difficulty.od)
+                        },
+                        ("Difficulty", "ApproachRate") => ar = value.parse().ok(),
+                        ("Difficulty", "SliderMultiplier") => {
+                            osu.difficulty.slider_multiplier =
+                                value.parse().unwrap_or(osu.difficulty.slider_multiplier)
+                        },
+                        ("Difficulty", "SliderTickRate") => {
+                            osu.difficulty.slider_tick_rate =
+                                value.parse().unwrap_or(osu.difficulty.slider_tick_rate)
+                        },
+                        _ => {},
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        // really old maps shares the od with ar
+        osu.difficulty.ar = ar.unwrap_or(osu.difficulty.od);
+
+        osu.timing_points.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Ok(osu)
+    }
+
+    pub fn circle_count(&self) -> u32 {
+        self.hit_objects.iter().filter(|o| o.is_circle()).count() as u32
+    }
+
+    pub fn slider_count(&self) -> u32 {
+        self.hit_objects.iter().filter(|o| o.is_slider()).count() as u32
+    }
+
+    pub fn spinner_count(&self) -> u32 {
+        self.hit_objects.iter().filter(|o| o.is_spinner()).count() as u32
+    }
+
+    pub fn hold_count(&self) -> u32 {
+        self.hit_objects.iter().filter(|o| o.is_hold()).count() as u32
+    }
+
+    pub fn object_count(&self) -> u32 {
+        self.hit_objects.len() as u32
+    }
+
+    /// max combo of the map in its own mode.
+    ///
+    /// mania holds only counts once (no hold ticks),
+    /// taiko only gets combo from the hits.
+    pub fn max_combo(&self) -> u32 {
+        self.hit_objects
+            .iter()
+            .map(|object| match (self.general.mode, object.kind) {
+                (1, HitObjectKind::Circle) => 1,
+                (1, _) => 0,
+                (2, HitObjectKind::Spinner { .. }) => 0,
+                (_, HitObjectKind::Slider { slides, length }) => {
+                    let ticks = self.slider_ticks(object.time, length);
+
+                    // head, every tick on every span, the repeats and the tail
+                    1 + slides * ticks + (slides - 1) + 1
+                },
+                _ => 1,
+            })
+            .sum()
+    }
+
+    /// in seconds, from the first object to the end of the last one.
+    pub fn total_length(&self) -> i32 {
+        let Some(first) = self.hit_objects.first() else {
+            return 0;
+        };
+
+        ((self.last_object_end() - first.time) / 1000.0).max(0.0) as i32
+    }
+
+    /// the bpm that lasts the longest, like the one shown on the website.
+    pub fn bpm(&self) -> f32 {
+        let end = self.last_object_end();
+
+        let uninherited: Vec<&TimingPoint> = self
+            .timing_points
+            .iter()
+            .filter(|t| t.uninherited)
+            .collect();
+
+        let mut durations: Vec<(f64, f64)> = Vec::new();
+
+        for (idx, point) in uninherited.iter().enumerate() {
+            let Some(bpm) = point.bpm() else {
+                continue;
+            };
+
+            let until = uninherited.get(idx + 1).map_or(end, |next| next.time);
+            // the first timing point counts from the start of the map
+            let from = if idx == 0 { 0.0 } else { point.time };
+            let duration = (until - from).max(0.0);
+
+            match durations.iter_mut().find(|(b, _)| (*b - bpm).abs() < 0.001) {
+                Some((_, total)) => *total += duration,
+                None => durations.push((bpm, duration)),
+            }
+        }
+
+        durations
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0.0, |(bpm, _)| bpm as f32)
+    }
+
+    /// ms per beat and slider velocity active at `time`.
+    fn timing_at(&self, time: f64) -> (f64, f64) {
+        let mut beat_length = self
+            .timing_points
+            .iter()
+            .find(|t| t.uninherited)
+            .map_or(1000.0, |t| t.beat_length);
+        let mut velocity = 1.0;
+
+        for point in self.timing_points.iter().take_while(|t| t.time <= time) {
+            if point.uninherited {
+                beat_length = point.beat_length;
+                velocity = 1.0;
+            } else {
+                velocity = point.slider_velocity();
+            }
+        }
+
+        (beat_length, velocity)
+    }
+
+    /// px per ms of a slider starting at `time`.
+    fn slider_speed(&self, time: f64) -> f64 {
+        let (beat_length, velocity) = self.timing_at(time);
+
+        100.0 * self.difficulty.slider_multiplier * velocity / beat_length
+    }
+
+    /// ticks on a single span of a slider.
+    fn slider_ticks(&self, time: f64, length: f64) -> u32 {
+        let (_, velocity) = self.timing_at(time);
+
+        let mut tick_distance =
+            100.0 * self.difficulty.slider_multiplier / self.difficulty.slider_tick_rate;
+
+        // since v8 the slider velocity affects the tick spacing too
+        if self.format_version >= 8 {
+            tick_distance *= velocity;
+        }
+
+        if !tick_distance.is_finite() || tick_distance <= 0.0 {
+            return 0;
+        }
+
+        // ticks too close to the end are dropped
+        let min_distance_from_end = self.slider_speed(time) * 10.0;
+        let usable = length - min_distance_from_end;
+
+        if usable <= 0.0 {
+            return 0;
+        }
+
+        ((usable / tick_distance).ceil() as u32).saturating_sub(1)
+    }
+
+    fn end_time(&self, object: &HitObject) -> f64 {
+        match object.kind {
+            HitObjectKind::Circle => object.time,
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => end_time,
+            HitObjectKind::Slider { slides, length } => {
+                let speed = self.slider_speed(object.time);
+
+                if speed.is_finite() && speed > 0.0 {
+                    object.time + length * slides as f64 / speed
+                } else {
+                    object.time
+                }
+            },
+        }
+    }
+
+    fn last_object_end(&self) -> f64 {
+        self.hit_objects
+            .iter()
+            .map(|object| self.end_time(object))
+            .fold(0.0, f64::max)
+    }
+}
@@ -0,0 +1,39 @@
+#[derive(Debug, Clone, Copy)]
+pub struct TimingPoint {
+    pub time: f64,
+    /// ms per beat on uninherited points, negative inverse slider velocity (percent) otherwise.
+    pub beat_length: f64,
+    pub meter: i32,
+    pub uninherited: bool,
+}
+
+impl TimingPoint {
+    /// `time,beatLength,meter,sampleSet,sampleIndex,volume,uninherited,effects`
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.split(',').map(str::trim);
+
+        let time = parts.next()?.parse().ok()?;
+        let beat_length: f64 = parts.next()?.parse().ok()?;
+        let meter = parts.next().and_then(|m| m.parse().ok()).unwrap_or(4);
+
+        // old maps doesn't have the flag, the sign tells them apart
+        let uninherited = parts
+            .nth(3)
+            .and_then(|u| u.parse::<i32>().ok())
+            .map_or(beat_length >= 0.0, |u| u == 1);
+
+        Some(Self { time, beat_length, meter, uninherited })
+    }
+
+    pub fn bpm(&self) -> Option<f64> {
+        (self.uninherited && self.beat_length > 0.0).then(|| 60000.0 / self.beat_length)
+    }
+
+    pub fn slider_velocity(&self) -> f64 {
+        if self.uninherited || self.beat_length >= 0.0 {
+            return 1.0;
+        }
+
+        (-100.0 / self.beat_length).clamp(0.1, 10.0)
+    }
+}
@@ -0,0 +1,161 @@
+use super::{Replay, ReplayFrame};
+use crate::constants::GameMode;
+
+/// two movements sharper than this are considered a snap.
+const SNAP_ANGLE_DEGREES: f32 = 10.0;
+/// and both of them need to travel at least this far (osu!pixels).
+const SNAP_MIN_DISTANCE: f32 = 8.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct ReplayStatistics {
+    pub unstable_rate: Option<f32>,
+    pub press_count: i32,
+    pub press_interval_mean: Option<f32>,
+    pub press_interval_stddev: Option<f32>,
+    pub hold_time_mean: Option<f32>,
+    pub hold_time_variance: Option<f32>,
+    pub snap_count: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct KeyPress {
+    time: i32,
+    hold: Option<i32>,
+}
+
+/// `hit_objects` are the start times of every object on the map,
+/// `hit_window` is the widest window that still counts as a hit.
+pub fn analyse(
+    replay: &Replay,
+    mode: GameMode,
+    hit_objects: &[i32],
+    hit_window: f32,
+    clock_rate: f64,
+) -> ReplayStatistics {
+    let presses = key_presses(&replay.frames, mode);
+
+    let intervals: Vec<f32> = presses
+        .windows(2)
+        .map(|w| (w[1].time - w[0].time) as f32)
+        .collect();
+    let holds: Vec<f32> = presses
+        .iter()
+        .filter_map(|p| p.hold)
+        .map(|h| h as f32)
+        .collect();
+
+    let unstable_rate = match mode.as_vanilla() {
+        // mania needs per-column matching, catch doesn't press anything
+        0 | 1 => unstable_rate(&presses, hit_objects, hit_window, clock_rate),
+        _ => None,
+    };
+
+    let snap_count = if mode.as_vanilla() == 0 { count_snaps(&replay.frames) } else { 0 };
+
+    ReplayStatistics {
+        unstable_rate,
+        press_count: presses.len() as i32,
+        press_interval_mean: mean(&intervals),
+        press_interval_stddev: variance(&intervals).map(f32::sqrt),
+        hold_time_mean: mean(&holds),
+        hold_time_variance: variance(&holds),
+        snap_count,
+    }
+}
+
+fn key_presses(frames: &[ReplayFrame], mode: GameMode) -> Vec<KeyPress> {
+    let mut presses: Vec<KeyPress> = Vec::new();
+    // index of the press that is currently held down for each key
+    let mut held: [Option<usize>; 32] = [None; 32];
+    let mut previous = 0u32;
+
+    for frame in frames.iter().filter(|f| f.time > 0) {
+        let state = frame.pressed_keys(mode);
+
+        for (bit, key) in held.iter_mut().enumerate() {
+            let mask = 1 << bit;
+
+            if state & mask != 0 && previous & mask == 0 {
+                *key = Some(presses.len());
+                presses.push(KeyPress { time: frame.time, hold: None });
+            } else if state & mask == 0
+                && previous & mask != 0
+                && let Some(idx) = key.take()
+            {
+                presses[idx].hold = Some(frame.time - presses[idx].time);
+            }
+        }
+
+        previous = state;
+    }
+
+    presses
+}
+
+fn unstable_rate(
+    presses: &[KeyPress],
+    hit_objects: &[i32],
+    hit_window: f32,
+    clock_rate: f64,
+) -> Option<f32> {
+    let mut errors = Vec::new();
+    let mut next_press = 0;
+
+    for &object in hit_objects {
+        // skip presses that are too early for this object
+        while next_press < presses.len()
+            && ((presses[next_press].time - object) as f32) < -hit_window
+        {
+            next_press += 1;
+        }
+
+        let Some(press) = presses.get(next_press) else {
+            break;
+        };
+
+        let error = (press.time - object) as f32;
+        if error.abs() <= hit_window {
+            errors.push(error);
+            next_press += 1;
+        }
+    }
+
+    // the hit errors are in map time, ur is shown in real time.
+    variance(&errors).map(|v| v.sqrt() * 10.0 / clock_rate as f32)
+}
+
+fn count_snaps(frames: &[ReplayFrame]) -> i32 {
+    let cos_threshold = SNAP_ANGLE_DEGREES.to_radians().cos();
+
+    frames
+        .windows(3)
+        .filter(|w| {
+            let (ax, ay) = (w[0].x - w[1].x, w[0].y - w[1].y);
+            let (bx, by) = (w[2].x - w[1].x, w[2].y - w[1].y);
+
+            let a = ax.hypot(ay);
+            let b = bx.hypot(by);
+
+            if a < SNAP_MIN_DISTANCE || b < SNAP_MIN_DISTANCE {
+                return false;
+            }
+
+            // the cursor went somewhere and came straight back
+            (ax * bx + ay * by) / (a * b) > cos_threshold
+        })
+        .count() as i32
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(values.iter().sum::<f32>() / values.len() as f32)
+}
+
+fn variance(values: &[f32]) -> Option<f32> {
+    let mean = mean(values)?;
+
+    Some(values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32)
+}
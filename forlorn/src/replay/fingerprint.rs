@@ -0,0 +1,116 @@
+use super::{Replay, ReplayFrame};
+use crate::constants::GameMode;
+
+/// how often the cursor gets sampled, in map time.
+const SAMPLE_INTERVAL_MS: i32 = 200;
+/// ~5 minutes of gameplay is more than enough to tell two replays apart.
+const MAX_SAMPLES: usize = 1500;
+/// shorter fingerprints match way too easily.
+const MIN_SAMPLES: usize = 50;
+
+/// osu!pixels per quantization step, so the playfield fits in a byte.
+const POSITION_STEP: f32 = 2.0;
+/// how far two quantized samples can be apart and still count as the same.
+const POSITION_TOLERANCE: i16 = 2;
+const PLAYFIELD_HEIGHT: f32 = 384.0;
+
+/// a normalized sample of a replay's cursor and keys.
+///
+/// the samples are taken relative to the first key press, so applying a
+/// time offset or re-encoding the frames doesn't change it (much).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    samples: Vec<[u8; 3]>,
+}
+
+impl Fingerprint {
+    /// std only. mania keeps the pressed columns in `x` and taiko/catch barely
+    /// move the cursor, so two honest plays of the same map would line up.
+    pub fn from_replay(replay: &Replay, mode: GameMode) -> Option<Self> {
+        if mode.as_vanilla() != 0 {
+            return None;
+        }
+
+        let frames: Vec<&ReplayFrame> = replay.frames.iter().filter(|f| f.time > 0).collect();
+
+        let anchor = frames.iter().find(|f| f.pressed_keys(mode) != 0)?.time;
+        let last = frames.last()?.time;
+
+        let mut samples = Vec::new();
+        let mut idx = 0;
+        let mut time = anchor;
+
+        while time <= last && samples.len() < MAX_SAMPLES {
+            while idx + 1 < frames.len() && frames[idx + 1].time <= time {
+                idx += 1;
+            }
+
+            let (x, y) = match frames.get(idx + 1) {
+                Some(next) => interpolate(frames[idx], next, time),
+                None => (frames[idx].x, frames[idx].y),
+            };
+
+            samples.push([quantize(x), quantize(y), frames[idx].pressed_keys(mode) as u8]);
+
+            time += SAMPLE_INTERVAL_MS;
+        }
+
+        (samples.len() >= MIN_SAMPLES).then_some(Self { samples })
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self {
+            samples: data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.samples.concat()
+    }
+
+    /// fraction of samples that line up, either as is or flipped vertically (hr).
+    pub fn similarity(&self, other: &Fingerprint) -> f32 {
+        let len = self.samples.len().min(other.samples.len());
+        if len < MIN_SAMPLES {
+            return 0.0;
+        }
+
+        let flipped_y = quantize(PLAYFIELD_HEIGHT) as i16;
+
+        let mut direct = 0;
+        let mut flipped = 0;
+
+        for (a, b) in self.samples.iter().zip(&other.samples) {
+            if a[2] != b[2] {
+                continue;
+            }
+
+            let dx = (a[0] as i16 - b[0] as i16).abs();
+            let dy = (a[1] as i16 - b[1] as i16).abs();
+            let dy_flipped = (a[1] as i16 - (flipped_y - b[1] as i16)).abs();
+
+            if dx <= POSITION_TOLERANCE && dy <= POSITION_TOLERANCE {
+                direct += 1;
+            }
+            if dx <= POSITION_TOLERANCE && dy_flipped <= POSITION_TOLERANCE {
+                flipped += 1;
+            }
+        }
+
+        direct.max(flipped) as f32 / len as f32
+    }
+}
+
+fn interpolate(a: &ReplayFrame, b: &ReplayFrame, time: i32) -> (f32, f32) {
+    if b.time <= a.time {
+        return (a.x, a.y);
+    }
+
+    let t = (time - a.time) as f32 / (b.time - a.time) as f32;
+
+    (a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+fn quantize(value: f32) -> u8 {
+    (value / POSITION_STEP).round().clamp(0.0, u8::MAX as f32) as u8
+}
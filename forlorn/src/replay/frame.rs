@@ -0,0 +1,75 @@
+use crate::constants::GameMode;
+
+/// the client writes this as the delta of the very last frame,
+/// the `keys` value of that frame is the rng seed used for the play.
+const SEED_FRAME_DELTA: i32 = -12345;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayFrame {
+    /// milliseconds since the previous frame
+    pub delta: i32,
+    /// absolute time of the frame, sum of every delta before it
+    pub time: i32,
+    pub x: f32,
+    pub y: f32,
+    pub keys: u32,
+}
+
+impl ReplayFrame {
+    /// `w|x|y|z`
+    pub fn parse(s: &str, time: i32) -> Option<Self> {
+        let mut parts = s.split('|');
+
+        let delta: i32 = parts.next()?.parse().ok()?;
+        let x: f32 = parts.next()?.parse().ok()?;
+        let y: f32 = parts.next()?.parse().ok()?;
+        // some clients writes this as a float, so we parse it loosely.
+        let keys = parts.next()?.parse::<f64>().ok()? as i64;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            delta,
+            time: time.saturating_add(delta),
+            x,
+            y,
+            keys: keys as u32,
+        })
+    }
+
+    pub fn is_seed(&self) -> bool {
+        self.delta == SEED_FRAME_DELTA
+    }
+
+    /// the pressed keys of this frame, as a bitmask.
+    pub fn pressed_keys(&self, mode: GameMode) -> u32 {
+        match mode.as_vanilla() {
+            // K1/K2 always comes with M1/M2, and we don't care about smoke
+            0 => self.keys & 0b11,
+            1 => self.keys & 0b1111,
+            // mania stores the pressed columns in `x`
+            3 => self.x as u32,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LifeBarPoint {
+    pub time: i32,
+    pub life: f32,
+}
+
+impl LifeBarPoint {
+    /// `u|v`
+    pub fn parse(s: &str) -> Option<Self> {
+        let (time, life) = s.split_once('|')?;
+
+        Some(Self {
+            time: time.parse().ok()?,
+            life: life.parse().ok()?,
+        })
+    }
+}
@@ -0,0 +1,10 @@
+pub mod analysis;
+pub mod fingerprint;
+pub mod frame;
+pub mod reader;
+pub mod writer;
+
+pub use analysis::ReplayStatistics;
+pub use fingerprint::Fingerprint;
+pub use frame::{LifeBarPoint, ReplayFrame};
+pub use reader::{Replay, ReplayHeader, split_lazer_payload};
@@ -0,0 +1,282 @@
+use std::io::{self, Cursor, Write};
+
+use anyhow::{Result, anyhow, bail};
+use lzma_rs::decompress::{Options, UnpackedSize};
+
+use super::frame::{LifeBarPoint, ReplayFrame};
+
+/// way past any real replay, the frames come straight from the client
+/// so a tiny upload shouldn't be able to inflate into gigabytes.
+const MAX_DECOMPRESSED_SIZE: usize = 32 * 1024 * 1024;
+
+/// the header of a full .osr file.
+///
+/// the client never sends this on submission,
+/// it only uploads the compressed frames.
+#[derive(Debug, Clone)]
+pub struct ReplayHeader {
+    pub mode: u8,
+    pub version: i32,
+    pub map_md5: String,
+    pub player_name: String,
+    pub replay_md5: String,
+    pub n300: u16,
+    pub n100: u16,
+    pub n50: u16,
+    pub ngeki: u16,
+    pub nkatu: u16,
+    pub nmiss: u16,
+    pub score: i32,
+    pub max_combo: u16,
+    pub perfect: bool,
+    pub mods: i32,
+    pub life_bar: Vec<LifeBarPoint>,
+    /// windows ticks
+    pub timestamp: i64,
+    pub online_score_id: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub header: Option<ReplayHeader>,
+    pub frames: Vec<ReplayFrame>,
+    pub seed: Option<i32>,
+}
+
+impl Replay {
+    /// parses the lzma compressed frames that the client uploads on submission.
+    pub fn from_frames(data: &[u8]) -> Result<Self> {
+        let (frames, seed) = decode_frames(data)?;
+
+        Ok(Self { header: None, frames, seed })
+    }
+
+    /// parses a full .osr file.
+    pub fn from_osr(data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+
+        let mode = reader.read_u8()?;
+        let version = reader.read_i32()?;
+        let map_md5 = reader.read_string()?;
+        let player_name = reader.read_string()?;
+        let replay_md5 = reader.read_string()?;
+        let n300 = reader.read_u16()?;
+        let n100 = reader.read_u16()?;
+        let n50 = reader.read_u16()?;
+        let ngeki = reader.read_u16()?;
+        let nkatu = reader.read_u16()?;
+        let nmiss = reader.read_u16()?;
+        let score = reader.read_i32()?;
+        let max_combo = reader.read_u16()?;
+        let perfect = reader.read_u8()? != 0;
+        let mods = reader.read_i32()?;
+
+        let life_bar = reader
+            .read_string()?
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .filter_map(LifeBarPoint::parse)
+            .collect();
+
+        let timestamp = reader.read_i64()?;
+
+        let compressed_len = reader.read_i32()?;
+        let compressed = reader.read_bytes(compressed_len.max(0) as usize)?;
+        let (frames, seed) = decode_frames(compressed)?;
+
+        let online_score_id = reader.read_i64().unwrap_or(0);
+
+        Ok(Self {
+            header: Some(ReplayHeader {
+                mode,
+                version,
+                map_md5,
+                player_name,
+                replay_md5,
+                n300,
+                n100,
+                n50,
+                ngeki,
+                nkatu,
+                nmiss,
+                score,
+                max_combo,
+                perfect,
+                mods,
+                life_bar,
+                timestamp,
+                online_score_id,
+            }),
+            frames,
+            seed,
+        })
+    }
+
+    /// absolute time of the last frame, in milliseconds.
+    pub fn duration(&self) -> i32 {
+        self.frames.last().map(|f| f.time).unwrap_or(0)
+    }
+}
+
+/// splits a replay stored the old way, with the lazer payload glued right
+/// after the compressed frames. `None` if there's nothing after the frames.
+pub fn split_lazer_payload(data: &[u8]) -> Result<Option<(&[u8], &[u8])>> {
+    // the decoder reads byte by byte, so it stops right at the end of the stream.
+    let (_, end) = decompress(data)?;
+
+    if end >= data.len() {
+        return Ok(None);
+    }
+
+    Ok(Some(data.split_at(end)))
+}
+
+/// writer that refuses to grow past `MAX_DECOMPRESSED_SIZE`.
+struct CappedWriter(Vec<u8>);
+
+impl Write for CappedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.0.len() + buf.len() > MAX_DECOMPRESSED_SIZE {
+            return Err(io::Error::other("decompressed replay too large"));
+        }
+
+        self.0.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// decompresses a lzma stream, returns the data & how many bytes the stream took.
+fn decompress(data: &[u8]) -> Result<(Vec<u8>, usize)> {
+    // properties (1 byte) & dictionary size (4 bytes) come first, all ones means unknown
+    let declared_size = data
+        .get(5..13)
+        .and_then(|b| b.try_into().ok())
+        .map(u64::from_le_bytes);
+
+    if let Some(size) = declared_size
+        && size != u64::MAX
+        && size > MAX_DECOMPRESSED_SIZE as u64
+    {
+        bail!("replay declares {size} bytes unpacked");
+    }
+
+    let options = Options {
+        unpacked_size: UnpackedSize::ReadFromHeader,
+        memlimit: Some(MAX_DECOMPRESSED_SIZE),
+        allow_incomplete: false,
+    };
+
+    let mut cursor = Cursor::new(data);
+    let mut out = CappedWriter(Vec::new());
+    lzma_rs::lzma_decompress_with_options(&mut cursor, &mut out, &options)?;
+
+    Ok((out.0, cursor.position() as usize))
+}
+
+fn decode_frames(compressed: &[u8]) -> Result<(Vec<ReplayFrame>, Option<i32>)> {
+    let (decompressed, _) = decompress(compressed)?;
+
+    let text = String::from_utf8_lossy(&decompressed);
+
+    let mut frames = Vec::new();
+    let mut seed = None;
+    let mut time = 0;
+
+    for raw in text.split(',').filter(|s| !s.is_empty()) {
+        let frame =
+            ReplayFrame::parse(raw, time).ok_or_else(|| anyhow!("malformed frame: {raw}"))?;
+
+        if frame.is_seed() {
+            seed = Some(frame.keys as i32);
+            continue;
+        }
+
+        time = frame.time;
+        frames.push(frame);
+    }
+
+    Ok((frames, seed))
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("unexpected end of replay at {}", self.pos))?;
+
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.read_bytes(N)?);
+
+        Ok(out)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_uleb128(&mut self) -> Result<usize> {
+        let mut value = 0usize;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as usize) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+            if shift >= usize::BITS {
+                return Err(anyhow!("uleb128 overflow at {}", self.pos));
+            }
+        }
+    }
+
+    /// 0x00 for empty strings, 0x0b followed by uleb128 length and utf-8 bytes otherwise.
+    fn read_string(&mut self) -> Result<String> {
+        match self.read_u8()? {
+            0x00 => Ok(String::new()),
+            0x0b => {
+                let len = self.read_uleb128()?;
+                Ok(String::from_utf8_lossy(self.read_bytes(len)?).to_string())
+            },
+            b => Err(anyhow!("invalid string marker 0x{b:02x}")),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::reader::ReplayHeader;
+
+/// ticks between 0001-01-01 and the unix epoch.
+const EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+
+impl ReplayHeader {
+    /// windows ticks of a unix timestamp in milliseconds.
+    pub fn ticks_from_unix_millis(millis: i64) -> i64 {
+        EPOCH_TICKS + millis * 10_000
+    }
+
+    /// a full .osr file with the header in front of the compressed frames.
+    pub fn write_osr(&self, compressed: &[u8]) -> Vec<u8> {
+        let mut writer = ByteWriter::with_capacity(compressed.len() + 256);
+
+        writer.write_u8(self.mode);
+        writer.write_i32(self.version);
+        writer.write_string(&self.map_md5);
+        writer.write_string(&self.player_name);
+        writer.write_string(&self.replay_md5);
+        writer.write_u16(self.n300);
+        writer.write_u16(self.n100);
+        writer.write_u16(self.n50);
+        writer.write_u16(self.ngeki);
+        writer.write_u16(self.nkatu);
+        writer.write_u16(self.nmiss);
+        writer.write_i32(self.score);
+        writer.write_u16(self.max_combo);
+        writer.write_u8(self.perfect as u8);
+        writer.write_i32(self.mods);
+
+        let life_bar = self
+            .life_bar
+            .iter()
+            .map(|point| format!("{}|{}", point.time, point.life))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        writer.write_string(&life_bar);
+        writer.write_i64(self.timestamp);
+        writer.write_i32(compressed.len() as i32);
+        writer.write_bytes(compressed);
+        writer.write_i64(self.online_score_id);
+
+        writer.buf
+    }
+}
+
+struct ByteWriter {
+    buf: Vec<u8>,
+}
+
+impl ByteWriter {
+    fn with_capacity(capacity: usize) -> Self {
+        Self { buf: Vec::with_capacity(capacity) }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    fn write_uleb128(&mut self, mut value: usize) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                self.write_u8(byte);
+                return;
+            }
+
+            self.write_u8(byte | 0x80);
+        }
+    }
+
+    /// same format `ByteReader::read_string` expects.
+    fn write_string(&mut self, s: &str) {
+        if s.is_empty() {
+            self.write_u8(0x00);
+            return;
+        }
+
+        self.write_u8(0x0b);
+        self.write_uleb128(s.len());
+        self.write_bytes(s.as_bytes());
+    }
+}
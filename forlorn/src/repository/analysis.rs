@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use crate::{infrastructure::database::DbPoolManager, models::ScoreAnalysis};
+
+pub async fn save(db: &DbPoolManager, analysis: &ScoreAnalysis) -> Result<()> {
+    sqlx::query(
+        "replace into score_analysis (
+            score_id, mode, unstable_rate, press_count,
+            press_interval_mean, press_interval_stddev,
+            hold_time_mean, hold_time_variance, snap_count,
+            flagged, flag_reasons, created_at
+        ) values (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(analysis.score_id)
+    .bind(analysis.mode)
+    .bind(analysis.unstable_rate)
+    .bind(analysis.press_count)
+    .bind(analysis.press_interval_mean)
+    .bind(analysis.press_interval_stddev)
+    .bind(analysis.hold_time_mean)
+    .bind(analysis.hold_time_variance)
+    .bind(analysis.snap_count)
+    .bind(analysis.flagged)
+    .bind(&analysis.flag_reasons)
+    .bind(analysis.created_at)
+    .execute(db.as_ref())
+    .await?;
+
+    Ok(())
+}
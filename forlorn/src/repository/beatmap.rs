@@ -2,7 +2,6 @@ use std::{collections::HashMap, sync::LazyLock};
 
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
-use tokio::sync::RwLock;
 
 use crate::{
     config::Config,
@@ -11,16 +10,12 @@ use crate::{
         omajinai::beatmap::{api_get_beatmaps, parse_beatmap_from_api, update_beatmap_from_api},
     },
     models::{Beatmap, BeatmapSetInfo},
+    repository::beatmap_cache::{BeatmapCache, Eviction},
 };
 
-// this cache has:
-// map_id as key
-// map_md5 as key
-// TODO: separate this?
-pub static BEATMAP_CACHE: LazyLock<RwLock<HashMap<String, Beatmap>>> =
-    LazyLock::new(|| RwLock::new(HashMap::new()));
+pub static BEATMAP_CACHE: LazyLock<BeatmapCache> = LazyLock::new(BeatmapCache::default);
 
-const PRIVATE_INITIAL_SET_ID: i32 = 1000000000;
+pub const PRIVATE_INITIAL_SET_ID: i32 = 1000000000;
 
 pub async fn fetch_by_md5(
     config: &Config,
@@ -32,17 +27,13 @@ pub async fn fetch_by_md5(
     }
 
     if let Some(b) = md5_from_database(db, md5).await? {
-        let mut cache = BEATMAP_CACHE.write().await;
-        cache.insert(b.id.to_string(), b.clone());
-        cache.insert(md5.to_string(), b.clone());
+        BEATMAP_CACHE.insert(b.clone());
 
         return Ok(Some(b));
     }
 
     if let Some(b) = md5_from_api(config, db, md5).await? {
-        let mut cache = BEATMAP_CACHE.write().await;
-        cache.insert(b.id.to_string(), b.clone());
-        cache.insert(md5.to_string(), b.clone());
+        BEATMAP_CACHE.insert(b.clone());
 
         return Ok(Some(b));
     }
@@ -56,17 +47,13 @@ pub async fn fetch_by_id(config: &Config, db: &DbPoolManager, id: &i32) -> Resul
     }
 
     if let Some(b) = id_from_database(db, id).await? {
-        let mut cache = BEATMAP_CACHE.write().await;
-        cache.insert(id.to_string(), b.clone());
-        cache.insert(b.md5.clone(), b.clone());
+        BEATMAP_CACHE.insert(b.clone());
 
         return Ok(Some(b));
     }
 
     if let Some(b) = id_from_api(config, db, id).await? {
-        let mut cache = BEATMAP_CACHE.write().await;
-        cache.insert(id.to_string(), b.clone());
-        cache.insert(b.md5.clone(), b.clone());
+        BEATMAP_CACHE.insert(b.clone());
 
         return Ok(Some(b));
     }
@@ -75,9 +62,7 @@ pub async fn fetch_by_id(config: &Config, db: &DbPoolManager, id: &i32) -> Resul
 }
 
 pub async fn md5_from_cache(md5: &str) -> Option<Beatmap> {
-    let cache = BEATMAP_CACHE.read().await;
-
-    cache.get(md5).cloned()
+    BEATMAP_CACHE.get_by_md5(md5)
 }
 
 pub async fn md5_from_database(db: &DbPoolManager, md5: &str) -> Result<Option<Beatmap>> {
@@ -118,20 +103,13 @@ pub async fn md5_from_database(db: &DbPoolManager, md5: &str) -> Result<Option<B
     .await?;
 
     if should_update_mapset(&set, last).await {
-        let mut cache = BEATMAP_CACHE.write().await;
-        cache.retain(|_, b| b.set_id != beatmap.set_id);
+        BEATMAP_CACHE.invalidate(Eviction::Set(beatmap.set_id));
 
         return Ok(None); // we force refetch from api
     }
 
-    let mut cache = BEATMAP_CACHE.write().await;
-    for b in set {
-        // might as well cache them all
-        cache.insert(b.id.to_string(), b.clone());
-        cache.insert(b.md5.clone(), b);
-    }
-
-    drop(cache);
+    // might as well cache them all
+    BEATMAP_CACHE.insert_many(set);
 
     Ok(Some(beatmap))
 }
@@ -171,8 +149,7 @@ async fn md5_from_api(config: &Config, db: &DbPoolManager, md5: &str) -> Result<
                         .execute(db.as_ref())
                         .await?;
                 }
-                let mut cache = BEATMAP_CACHE.write().await;
-                cache.remove(md5);
+                BEATMAP_CACHE.invalidate(Eviction::Md5(md5.to_string()));
             }
 
             return Ok(None);
@@ -370,9 +347,7 @@ pub async fn fetch_set_by_md5(db: &DbPoolManager, md5: &String) -> Result<Option
 }
 
 pub async fn id_from_cache(id: &i32) -> Option<Beatmap> {
-    let cache = BEATMAP_CACHE.read().await;
-
-    cache.get(&id.to_string()).cloned()
+    BEATMAP_CACHE.get_by_id(*id)
 }
 
 pub async fn id_from_database(db: &DbPoolManager, map_id: &i32) -> Result<Option<Beatmap>> {
@@ -413,20 +388,13 @@ pub async fn id_from_database(db: &DbPoolManager, map_id: &i32) -> Result<Option
     .await?;
 
     if should_update_mapset(&set, last).await {
-        let mut cache = BEATMAP_CACHE.write().await;
-        cache.retain(|_, b| b.set_id != beatmap.set_id);
+        BEATMAP_CACHE.invalidate(Eviction::Set(beatmap.set_id));
 
         return Ok(None); // we force refetch from api
     }
 
-    let mut cache = BEATMAP_CACHE.write().await;
-    for b in set {
-        // might as well cache them all
-        cache.insert(b.id.to_string(), b.clone());
-        cache.insert(b.md5.clone(), b);
-    }
-
-    drop(cache);
+    // might as well cache them all
+    BEATMAP_CACHE.insert_many(set);
 
     Ok(Some(beatmap))
 }
@@ -467,8 +435,7 @@ async fn id_from_api(config: &Config, db: &DbPoolManager, map_id: &i32) -> Resul
                         .await?;
                 }
 
-                let mut cache = BEATMAP_CACHE.write().await;
-                cache.remove(&map_id.to_string());
+                BEATMAP_CACHE.invalidate(Eviction::Id(*map_id));
             }
 
             return Ok(None);
@@ -507,6 +474,33 @@ async fn id_from_api(config: &Config, db: &DbPoolManager, map_id: &i32) -> Resul
     Ok(to_save.into_iter().find(|b| b.id == *map_id))
 }
 
+/// only the columns that can be read from the .osu file.
+pub async fn update_metadata(db: &DbPoolManager, beatmap: &Beatmap) -> Result<()> {
+    sqlx::query(
+        "update maps set artist = ?, title = ?, version = ?, creator = ?, total_length = ?, \
+         max_combo = ?, mode = ?, bpm = ?, cs = ?, ar = ?, od = ?, hp = ? where md5 = ?",
+    )
+    .bind(&beatmap.artist)
+    .bind(&beatmap.title)
+    .bind(&beatmap.version)
+    .bind(&beatmap.creator)
+    .bind(beatmap.total_length)
+    .bind(beatmap.max_combo)
+    .bind(beatmap.mode)
+    .bind(beatmap.bpm)
+    .bind(beatmap.cs)
+    .bind(beatmap.ar)
+    .bind(beatmap.od)
+    .bind(beatmap.hp)
+    .bind(&beatmap.md5)
+    .execute(db.as_ref())
+    .await?;
+
+    BEATMAP_CACHE.insert(beatmap.clone());
+
+    Ok(())
+}
+
 async fn save(db: &DbPoolManager, beatmaps: &[Beatmap]) -> Result<()> {
     if beatmaps.is_empty() {
         return Ok(());
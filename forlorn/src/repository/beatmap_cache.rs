@@ -0,0 +1,341 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::{constants::RankedStatus, models::Beatmap};
+
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// how long a map is trusted before it's read again. statuses that are
+/// likely to change soon don't stay around for long.
+fn ttl(status: i32) -> Duration {
+    match RankedStatus::from_i32(status) {
+        RankedStatus::Ranked | RankedStatus::Approved => Duration::from_secs(24 * 60 * 60),
+        RankedStatus::Loved => Duration::from_secs(6 * 60 * 60),
+        RankedStatus::Qualified => Duration::from_secs(10 * 60),
+        RankedStatus::Pending | RankedStatus::UpdateAvailable => Duration::from_secs(5 * 60),
+    }
+}
+
+struct Entry {
+    beatmap: Beatmap,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    capacity: usize,
+    tick: u64,
+    by_md5: HashMap<String, Entry>,
+    md5_by_id: HashMap<i32, String>,
+    /// last_used -> md5, the first one is the next to go
+    recency: BTreeMap<u64, String>,
+}
+
+impl Inner {
+    fn touch(&mut self, md5: &str) -> Option<Beatmap> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        let entry = self.by_md5.get_mut(md5)?;
+
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(tick, md5.to_string());
+        entry.last_used = tick;
+
+        Some(entry.beatmap.clone())
+    }
+
+    fn remove(&mut self, md5: &str) -> Option<Entry> {
+        let entry = self.by_md5.remove(md5)?;
+
+        self.recency.remove(&entry.last_used);
+
+        // the id might point to a newer version of the map by now
+        if self.md5_by_id.get(&entry.beatmap.id).map(String::as_str) == Some(md5) {
+            self.md5_by_id.remove(&entry.beatmap.id);
+        }
+
+        Some(entry)
+    }
+}
+
+/// maps dropped on purpose, the other replicas are told to drop them too.
+/// the lru making room doesn't count, that's local to each cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Eviction {
+    Md5(String),
+    Id(i32),
+    Set(i32),
+    All,
+}
+
+impl Eviction {
+    pub fn parse(payload: &str) -> Option<Self> {
+        let (kind, value) = payload.split_once(':').unwrap_or((payload, ""));
+
+        match kind {
+            "md5" => Some(Self::Md5(value.to_string())),
+            "id" => value.parse().ok().map(Self::Id),
+            "set" => value.parse().ok().map(Self::Set),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Eviction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Md5(md5) => write!(f, "md5:{md5}"),
+            Self::Id(id) => write!(f, "id:{id}"),
+            Self::Set(set_id) => write!(f, "set:{set_id}"),
+            Self::All => write!(f, "all"),
+        }
+    }
+}
+
+/// a cached map, for the admin api.
+#[derive(Serialize)]
+pub struct CachedBeatmap {
+    pub id: i32,
+    pub set_id: i32,
+    pub md5: String,
+    pub status: i32,
+    pub expires_in: u64,
+}
+
+#[derive(Serialize)]
+pub struct CacheStats {
+    pub size: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// maps by md5, with an id index on the side. holds up to `capacity` maps
+/// and drops the least recently used one when it's full.
+pub struct BeatmapCache {
+    inner: Mutex<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    broadcast: OnceLock<UnboundedSender<Eviction>>,
+}
+
+impl Default for BeatmapCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl BeatmapCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner { capacity, ..Default::default() }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            broadcast: OnceLock::new(),
+        }
+    }
+
+    /// evictions made through `invalidate` from now on, only the first caller gets them.
+    pub fn subscribe_evictions(&self) -> UnboundedReceiver<Eviction> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let _ = self.broadcast.set(tx);
+
+        rx
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity;
+
+        self.evict(&mut inner);
+    }
+
+    pub fn get_by_md5(&self, md5: &str) -> Option<Beatmap> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let expired = inner
+            .by_md5
+            .get(md5)
+            .is_some_and(|entry| entry.expires_at <= Instant::now());
+
+        if expired {
+            inner.remove(md5);
+        }
+
+        match inner.touch(md5) {
+            Some(beatmap) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(beatmap)
+            },
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            },
+        }
+    }
+
+    pub fn get_by_id(&self, id: i32) -> Option<Beatmap> {
+        let md5 = self.inner.lock().unwrap().md5_by_id.get(&id).cloned();
+
+        match md5 {
+            Some(md5) => self.get_by_md5(&md5),
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            },
+        }
+    }
+
+    pub fn insert(&self, beatmap: Beatmap) {
+        self.insert_many([beatmap]);
+    }
+
+    /// a whole set goes in under one lock.
+    pub fn insert_many(&self, beatmaps: impl IntoIterator<Item = Beatmap>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        for beatmap in beatmaps {
+            inner.remove(&beatmap.md5);
+
+            inner.tick += 1;
+            let tick = inner.tick;
+            let md5 = beatmap.md5.clone();
+
+            inner.md5_by_id.insert(beatmap.id, md5.clone());
+            inner.recency.insert(tick, md5.clone());
+            inner.by_md5.insert(
+                md5,
+                Entry {
+                    expires_at: Instant::now() + ttl(beatmap.status),
+                    beatmap,
+                    last_used: tick,
+                },
+            );
+        }
+
+        self.evict(&mut inner);
+    }
+
+    fn evict(&self, inner: &mut Inner) {
+        while inner.by_md5.len() > inner.capacity {
+            let Some((_, md5)) = inner.recency.pop_first() else {
+                break;
+            };
+
+            inner.remove(&md5);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// drops maps here & on every other replica.
+    pub fn invalidate(&self, eviction: Eviction) -> usize {
+        let removed = self.apply(&eviction);
+
+        if let Some(tx) = self.broadcast.get() {
+            let _ = tx.send(eviction);
+        }
+
+        removed
+    }
+
+    /// drops maps here only, for evictions that came from another replica.
+    pub fn apply(&self, eviction: &Eviction) -> usize {
+        match eviction {
+            Eviction::Md5(md5) => self.remove_md5(md5) as usize,
+            Eviction::Id(id) => self.remove_id(*id) as usize,
+            Eviction::Set(set_id) => self.remove_set(*set_id),
+            Eviction::All => self.clear(),
+        }
+    }
+
+    pub fn remove_md5(&self, md5: &str) -> bool {
+        self.inner.lock().unwrap().remove(md5).is_some()
+    }
+
+    pub fn remove_id(&self, id: i32) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        match inner.md5_by_id.get(&id).cloned() {
+            Some(md5) => inner.remove(&md5).is_some(),
+            None => false,
+        }
+    }
+
+    /// returns how many maps of the set were dropped.
+    pub fn remove_set(&self, set_id: i32) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+
+        let md5s: Vec<String> = inner
+            .by_md5
+            .values()
+            .filter(|entry| entry.beatmap.set_id == set_id)
+            .map(|entry| entry.beatmap.md5.clone())
+            .collect();
+
+        for md5 in &md5s {
+            inner.remove(md5);
+        }
+
+        md5s.len()
+    }
+
+    pub fn clear(&self) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let size = inner.by_md5.len();
+
+        inner.by_md5.clear();
+        inner.md5_by_id.clear();
+        inner.recency.clear();
+
+        size
+    }
+
+    /// most recently used first.
+    pub fn entries(&self, limit: usize) -> Vec<CachedBeatmap> {
+        let inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+
+        inner
+            .recency
+            .values()
+            .rev()
+            .filter_map(|md5| inner.by_md5.get(md5))
+            .take(limit)
+            .map(|entry| CachedBeatmap {
+                id: entry.beatmap.id,
+                set_id: entry.beatmap.set_id,
+                md5: entry.beatmap.md5.clone(),
+                status: entry.beatmap.status,
+                expires_in: entry.expires_at.saturating_duration_since(now).as_secs(),
+            })
+            .collect()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.inner.lock().unwrap();
+
+        CacheStats {
+            size: inner.by_md5.len(),
+            capacity: inner.capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
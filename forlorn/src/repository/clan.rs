@@ -1,6 +1,10 @@
 use anyhow::Result;
 
-use crate::{infrastructure::database::DbPoolManager, models::Clan};
+use crate::{
+    constants::{LAZER_ONLINE_CHECKSUM, SubmissionStatus},
+    infrastructure::database::DbPoolManager,
+    models::{Clan, ClanMapRanking, ClanModeStats},
+};
 
 pub async fn fetch_by_id(db: &DbPoolManager, clan_id: i32) -> Result<Option<Clan>> {
     let clan = sqlx::query_as::<_, Clan>("select * from clans where id = ?")
@@ -10,3 +14,57 @@ pub async fn fetch_by_id(db: &DbPoolManager, clan_id: i32) -> Result<Option<Clan
 
     Ok(clan)
 }
+
+/// clans on the map, ranked by the sum of their members' best scores.
+/// `order_by` has to be either `pp` or `score`.
+pub async fn fetch_map_ranking(
+    db: &DbPoolManager,
+    map_md5: &str,
+    mode: i32,
+    order_by: &str,
+    limit: u32,
+) -> Result<Vec<ClanMapRanking>> {
+    let query = format!(
+        "select c.id as clan_id, c.name, c.tag, count(*) as members,
+                cast(sum(s.pp) as double) as pp, cast(sum(s.score) as signed) as score
+         from scores s
+         inner join users u on u.id = s.userid
+         inner join clans c on c.id = u.clan_id
+         where s.map_md5 = ? and s.mode = ? and s.status = ? and s.online_checksum != ?
+         and u.priv & 1
+         group by c.id, c.name, c.tag
+         order by {order_by} desc, c.id
+         limit ?"
+    );
+
+    let ranking = sqlx::query_as::<_, ClanMapRanking>(&query)
+        .bind(map_md5)
+        .bind(mode)
+        .bind(SubmissionStatus::Best.as_i32())
+        .bind(LAZER_ONLINE_CHECKSUM)
+        .bind(limit)
+        .fetch_all(db.as_ref())
+        .await?;
+
+    Ok(ranking)
+}
+
+/// the stats of every unrestricted member added up, per mode.
+pub async fn fetch_mode_totals(db: &DbPoolManager, clan_id: i32) -> Result<Vec<ClanModeStats>> {
+    let totals = sqlx::query_as::<_, ClanModeStats>(
+        "select st.mode, count(*) as members,
+                cast(sum(st.pp) as signed) as pp, cast(sum(st.rscore) as signed) as rscore,
+                cast(sum(st.tscore) as signed) as tscore, cast(sum(st.plays) as signed) as plays,
+                cast(sum(st.playtime) as signed) as playtime, cast(avg(st.acc) as double) as acc
+         from stats st
+         inner join users u on u.id = st.id
+         where u.clan_id = ? and u.priv & 1
+         group by st.mode
+         order by st.mode",
+    )
+    .bind(clan_id)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(totals)
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use crate::infrastructure::database::DbPoolManager;
+
+/// (score_id, userid, fingerprint) of the latest `limit` replays of other players on the map.
+pub async fn fetch_others_by_map(
+    db: &DbPoolManager,
+    map_md5: &str,
+    user_id: i32,
+    limit: u32,
+) -> Result<Vec<(u64, i32, Vec<u8>)>> {
+    let fingerprints = sqlx::query_as::<_, (u64, i32, Vec<u8>)>(
+        "select score_id, userid, fingerprint from score_fingerprints
+         where map_md5 = ? and userid != ?
+         order by created_at desc limit ?",
+    )
+    .bind(map_md5)
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(fingerprints)
+}
+
+pub async fn insert(
+    db: &DbPoolManager,
+    score_id: u64,
+    map_md5: &str,
+    user_id: i32,
+    fingerprint: &[u8],
+) -> Result<()> {
+    sqlx::query(
+        "insert into score_fingerprints (score_id, map_md5, userid, fingerprint) values (?, ?, ?, ?)",
+    )
+    .bind(score_id)
+    .bind(map_md5)
+    .bind(user_id)
+    .bind(fingerprint)
+    .execute(db.as_ref())
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use sqlx::types::Json;
+
+use crate::{
+    dto::v1::lazer::SubmitLazerScore,
+    infrastructure::database::{DbConnection, DbPoolManager},
+};
+
+pub async fn exists(db: &DbPoolManager, checksum: &str) -> Result<bool> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "select exists(select 1 from lazer_scores where checksum = ?)",
+    )
+    .bind(checksum)
+    .fetch_one(db.as_ref())
+    .await?;
+
+    Ok(exists)
+}
+
+/// the lazer side of a score, the rest lives in `scores`.
+pub async fn insert(
+    conn: &mut DbConnection,
+    score_id: u64,
+    checksum: &str,
+    submission: &SubmitLazerScore,
+) -> Result<()> {
+    sqlx::query(
+        "insert into lazer_scores (score_id, checksum, total_score, classic_total_score, mods, statistics)
+         values (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(score_id)
+    .bind(checksum)
+    .bind(submission.total_score)
+    .bind(submission.classic_total_score)
+    .bind(Json(&submission.mods))
+    .bind(Json(&submission.statistics))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -1,12 +1,72 @@
 use anyhow::Result;
+use sqlx::{MySql, mysql::MySqlArguments, query::QueryAs};
 
 use crate::{
-    constants::LeaderboardType, infrastructure::database::DbPoolManager, models::LeaderboardScore,
+    constants::{LAZER_ONLINE_CHECKSUM, LeaderboardType},
+    infrastructure::database::DbPoolManager,
+    models::{LeaderboardScore, Tournament},
 };
 
+fn metric_column(alias: &str, scoring_metric: &str, lazer: bool) -> String {
+    // lazer scores are ranked by their standardised score
+    if lazer && scoring_metric == "score" {
+        format!("l{alias}.total_score")
+    } else {
+        format!("{alias}.{scoring_metric}")
+    }
+}
+
+/// (join, where) of the scores a leaderboard is built from, bound by `bind_filter`.
+///
+/// stable leaderboards are made of the best scores, lazer ones are ranked
+/// on their own, so it's the top lazer score of every player instead.
+fn score_pool(scoring_metric: &str, lazer: bool) -> (&'static str, String) {
+    if !lazer {
+        return ("", "s.status = 2 and s.online_checksum != ?".to_string());
+    }
+
+    (
+        "inner join lazer_scores ls on ls.score_id = s.id",
+        format!(
+            "s.status in (1, 2) and s.online_checksum = ? \
+             and s.id = (select s2.id from scores s2 \
+             inner join lazer_scores ls2 on ls2.score_id = s2.id \
+             where s2.userid = s.userid and s2.map_md5 = s.map_md5 \
+             and s2.mode = s.mode and s2.status in (1, 2) \
+             order by {} desc, s2.id limit 1)",
+            metric_column("s2", scoring_metric, true)
+        ),
+    )
+}
+
+/// where of the scores a tournament is played with, the best one every player
+/// set on the map inside the window. bound by `bind_tournament`.
+pub fn tournament_pool(scoring_metric: &str) -> String {
+    format!(
+        "s.id = (select s2.id from scores s2 \
+         where s2.userid = s.userid and s2.map_md5 = s.map_md5 \
+         and s2.mode = s.mode and s2.status in (1, 2) \
+         and s2.play_time >= ? and s2.play_time < ? and (? is null or s2.mods = ?) \
+         and s2.online_checksum != ? \
+         order by s2.{scoring_metric} desc, s2.id limit 1)"
+    )
+}
+
+pub fn bind_tournament<'q, O>(
+    query: QueryAs<'q, MySql, O, MySqlArguments>,
+    tournament: &'q Tournament,
+) -> QueryAs<'q, MySql, O, MySqlArguments> {
+    query
+        .bind(tournament.starts_at)
+        .bind(tournament.ends_at)
+        .bind(tournament.mods)
+        .bind(tournament.mods)
+        .bind(LAZER_ONLINE_CHECKSUM)
+}
+
 fn cheat_columns(is_refx: bool) -> &'static str {
     if is_refx {
-        ", s.aim_assist_type, s.maple_values, s.aim_value, s.ar_value, s.arc, s.cs, s.tw, s.twval, s.hdr, s.score"
+        ", s.aim_assist_type, s.maple_values, s.aim_value, s.ar_value, s.arc, s.cs, s.tw, s.twval, s.hdr"
     } else {
         // of course.
         ", NULL as aim_assist_type, \
@@ -17,127 +77,148 @@ fn cheat_columns(is_refx: bool) -> &'static str {
            NULL as cs, \
            NULL as tw, \
            NULL as twval, \
-           NULL as hdr, \
-           NULL as score"
+           NULL as hdr"
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub async fn fetch_leaderboard_scores(
-    db: &DbPoolManager,
-    map_md5: &str,
-    mode: i32,
-    user_id: i32,
-    leaderboard_type: LeaderboardType,
-    mods: Option<i32>,
-    country: Option<&str>,
-    friend_ids: Option<&[i32]>,
-    scoring_metric: &str,
-    is_refx: bool,
-) -> Result<Vec<LeaderboardScore>> {
-    let cheat_val = cheat_columns(is_refx);
+/// everything that decides which scores make up a leaderboard.
+/// the top list, the personal best & its rank all go through it.
+pub struct LeaderboardFilter<'a> {
+    pub map_md5: &'a str,
+    pub mode: i32,
+    /// the viewer, restricted players still see their own scores
+    pub user_id: i32,
+    pub leaderboard_type: LeaderboardType,
+    /// already reduced to its leaderboard equivalent
+    pub mods: i32,
+    /// the bits of the mods that tell mods boards apart
+    pub mods_mask: i32,
+    pub country: &'a str,
+    pub friend_ids: &'a [i32],
+    pub clan_id: i32,
+    pub scoring_metric: &'a str,
+    pub lazer: bool,
+    /// set on tournament boards, it replaces the usual pool
+    pub tournament: Option<&'a Tournament>,
+}
 
-    let base_query = format!(
-        "select s.id, CAST(s.{} AS DOUBLE) as preferred_metric, \
-         s.max_combo, s.n50, s.n100, s.n300, \
-         s.nmiss, s.nkatu, s.ngeki, s.perfect, s.mods, s.clock_rate, \
-         unix_timestamp(s.play_time) as play_time, u.id as userid, \
-         coalesce(concat('[', c.tag, '] ', u.name), u.name) as name \
-         {} \
-         from scores s \
-         inner join users u on u.id = s.userid \
-         left join clans c on c.id = u.clan_id \
-         where s.map_md5 = ? and s.status = 2 \
-         and (u.priv & 1 or u.id = ?) and s.mode = ? \
-         and s.online_checksum != 'lazer_score'",
-        scoring_metric, cheat_val
-    );
+impl LeaderboardFilter<'_> {
+    fn metric(&self) -> String {
+        metric_column("s", self.scoring_metric, self.lazer)
+    }
 
-    let (query_with_filter, needs_friends_binding) = match leaderboard_type {
-        LeaderboardType::Mods => (format!("{} and s.mods = ?", base_query), false),
-        LeaderboardType::Friends => {
-            if let Some(friends) = friend_ids {
-                if friends.is_empty() {
-                    (format!("{} and s.userid = ?", base_query), false)
-                } else {
-                    let placeholders = friends.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-                    (
-                        format!("{} and s.userid in ({})", base_query, placeholders),
-                        true,
-                    )
-                }
-            } else {
-                (format!("{} and s.userid = ?", base_query), false)
-            }
-        },
-        LeaderboardType::Country => (format!("{} and u.country = ?", base_query), false),
-        _ => (base_query, false),
-    };
+    /// from & where of the board, bound by `bind_filter`.
+    fn clause(&self) -> String {
+        let (pool_join, pool_clause) = match self.tournament {
+            Some(_) => ("", tournament_pool(self.scoring_metric)),
+            None => score_pool(self.scoring_metric, self.lazer),
+        };
+
+        let type_clause = match self.leaderboard_type {
+            LeaderboardType::Mods => " and s.mods & ? = ?".to_string(),
+            LeaderboardType::Friends if !self.friend_ids.is_empty() => {
+                let placeholders = vec!["?"; self.friend_ids.len()].join(",");
+                format!(" and s.userid in ({placeholders})")
+            },
+            LeaderboardType::Friends => " and s.userid = ?".to_string(),
+            LeaderboardType::Country => " and u.country = ?".to_string(),
+            LeaderboardType::Clan if self.clan_id != 0 => " and u.clan_id = ?".to_string(),
+            // clanless, so it's just them
+            LeaderboardType::Clan => " and s.userid = ?".to_string(),
+            _ => String::new(),
+        };
+
+        format!(
+            "from scores s \
+             inner join users u on u.id = s.userid \
+             left join clans c on c.id = u.clan_id \
+             {pool_join} \
+             where s.map_md5 = ? and {pool_clause} \
+             and (u.priv & 1 or u.id = ?) and s.mode = ?{type_clause}"
+        )
+    }
+}
 
-    let final_query = format!(
-        "{} order by preferred_metric desc limit 50",
-        query_with_filter
-    );
+fn bind_filter<'q, O>(
+    query: QueryAs<'q, MySql, O, MySqlArguments>,
+    filter: &'q LeaderboardFilter,
+) -> QueryAs<'q, MySql, O, MySqlArguments> {
+    let mut query = query.bind(filter.map_md5);
 
-    let mut query = sqlx::query_as::<_, LeaderboardScore>(&final_query)
-        .bind(map_md5)
-        .bind(user_id)
-        .bind(mode);
+    query = match filter.tournament {
+        Some(tournament) => bind_tournament(query, tournament),
+        None => query.bind(LAZER_ONLINE_CHECKSUM),
+    };
 
-    match leaderboard_type {
-        LeaderboardType::Mods => {
-            query = query.bind(mods.unwrap_or(0));
-        },
-        LeaderboardType::Friends => {
-            if needs_friends_binding {
-                if let Some(friends) = friend_ids {
-                    for friend_id in friends {
-                        query = query.bind(friend_id);
-                    }
-                }
-            } else {
-                query = query.bind(user_id);
+    query = query.bind(filter.user_id).bind(filter.mode);
+
+    match filter.leaderboard_type {
+        LeaderboardType::Mods => query = query.bind(filter.mods_mask).bind(filter.mods),
+        LeaderboardType::Friends if !filter.friend_ids.is_empty() => {
+            for friend_id in filter.friend_ids {
+                query = query.bind(friend_id);
             }
         },
-        LeaderboardType::Country => {
-            query = query.bind(country.unwrap_or(""));
-        },
+        LeaderboardType::Friends => query = query.bind(filter.user_id),
+        LeaderboardType::Country => query = query.bind(filter.country),
+        LeaderboardType::Clan if filter.clan_id != 0 => query = query.bind(filter.clan_id),
+        LeaderboardType::Clan => query = query.bind(filter.user_id),
         _ => {},
-    };
+    }
+
+    query
+}
+
+fn score_columns(filter: &LeaderboardFilter, is_refx: bool) -> String {
+    format!(
+        "s.id, CAST({} AS DOUBLE) as preferred_metric, \
+         s.pp, s.acc, s.grade, s.score, \
+         s.max_combo, s.n50, s.n100, s.n300, \
+         s.nmiss, s.nkatu, s.ngeki, s.perfect, s.mods, s.clock_rate, \
+         unix_timestamp(s.play_time) as play_time, u.id as userid, \
+         u.name, c.tag as clan_tag \
+         {}",
+        filter.metric(),
+        cheat_columns(is_refx)
+    )
+}
 
-    let scores = query.fetch_all(db.as_ref()).await?;
+pub async fn fetch_leaderboard_scores(
+    db: &DbPoolManager,
+    filter: &LeaderboardFilter<'_>,
+    is_refx: bool,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<LeaderboardScore>> {
+    let query = format!(
+        "select {} {} order by preferred_metric desc, s.id limit ? offset ?",
+        score_columns(filter, is_refx),
+        filter.clause()
+    );
+
+    let scores = bind_filter(sqlx::query_as::<_, LeaderboardScore>(&query), filter)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(db.as_ref())
+        .await?;
 
     Ok(scores)
 }
 
+/// the viewer's own entry on the board.
 pub async fn fetch_personal_best_score(
     db: &DbPoolManager,
-    map_md5: &str,
-    mode: i32,
-    user_id: i32,
-    scoring_metric: &str,
+    filter: &LeaderboardFilter<'_>,
     is_refx: bool,
 ) -> Result<Option<LeaderboardScore>> {
-    let cheat_val = cheat_columns(is_refx);
-
     let query = format!(
-        "select s.id, CAST(s.{} AS DOUBLE) as preferred_metric, \
-         s.max_combo, s.n50, s.n100, s.n300, \
-         s.nmiss, s.nkatu, s.ngeki, s.perfect, s.mods, s.clock_rate, \
-         unix_timestamp(s.play_time) as play_time, u.id as userid, u.name as name \
-         {} \
-         from scores s \
-         inner join users u on u.id = s.userid \
-         where s.map_md5 = ? and s.mode = ? \
-         and s.userid = ? and s.status = 2 \
-         order by preferred_metric desc limit 1",
-        scoring_metric, cheat_val
+        "select {} {} and s.userid = ? order by preferred_metric desc limit 1",
+        score_columns(filter, is_refx),
+        filter.clause()
     );
 
-    let pb = sqlx::query_as::<_, LeaderboardScore>(&query)
-        .bind(map_md5)
-        .bind(mode)
-        .bind(user_id)
+    let pb = bind_filter(sqlx::query_as::<_, LeaderboardScore>(&query), filter)
+        .bind(filter.user_id)
         .fetch_optional(db.as_ref())
         .await?;
 
@@ -146,23 +227,17 @@ pub async fn fetch_personal_best_score(
 
 pub async fn fetch_personal_best_rank(
     db: &DbPoolManager,
-    map_md5: &str,
-    mode: i32,
+    filter: &LeaderboardFilter<'_>,
     score_value: f64,
-    scoring_metric: &str,
 ) -> Result<i32> {
     let query = format!(
-        "select count(*) from scores s \
-         inner join users u on u.id = s.userid \
-         where s.map_md5 = ? and s.mode = ? \
-         and s.status = 2 and u.priv & 1 \
-         and s.{} > ?",
-        scoring_metric
+        "select count(*) {} and u.id != ? and {} > ?",
+        filter.clause(),
+        filter.metric()
     );
 
-    let count: i64 = sqlx::query_scalar(&query)
-        .bind(map_md5)
-        .bind(mode)
+    let (count,): (i64,) = bind_filter(sqlx::query_as(&query), filter)
+        .bind(filter.user_id)
         .bind(score_value)
         .fetch_one(db.as_ref())
         .await?;
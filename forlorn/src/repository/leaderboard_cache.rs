@@ -0,0 +1,86 @@
+use anyhow::Result;
+use redis::AsyncCommands;
+
+use crate::{
+    constants::LeaderboardType, infrastructure::redis::RedisConnectionManager,
+    models::LeaderboardScore, repository::leaderboard::LeaderboardFilter,
+};
+
+// invalidation is explicit, the ttl is only there so a missed one can't stick around forever
+const CACHE_TTL_SECS: u64 = 600;
+
+fn map_keys(map_md5: &str) -> String {
+    format!("refx:leaderboard_keys:{map_md5}")
+}
+
+/// key of a cached top list, made of the same filter the list is queried with.
+pub fn cache_key(filter: &LeaderboardFilter, is_refx: bool, size: u32) -> String {
+    // friend & clan boards aren't cached, so this is all that narrows a board down
+    let scope = match filter.leaderboard_type {
+        LeaderboardType::Mods => filter.mods.to_string(),
+        LeaderboardType::Country => filter.country.to_string(),
+        _ => String::new(),
+    };
+
+    format!(
+        "refx:leaderboard:{}:{}:{}:{scope}:{}:{}:{}:{size}",
+        filter.map_md5,
+        filter.mode,
+        filter.leaderboard_type as i32,
+        filter.scoring_metric,
+        is_refx as u8,
+        filter.lazer as u8
+    )
+}
+
+pub async fn fetch(
+    redis: &RedisConnectionManager,
+    key: &str,
+) -> Result<Option<Vec<LeaderboardScore>>> {
+    let mut conn = redis.lock().await;
+
+    let cached: Option<String> = conn.get(key).await?;
+
+    match cached {
+        Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn store(
+    redis: &RedisConnectionManager,
+    map_md5: &str,
+    key: &str,
+    scores: &[LeaderboardScore],
+) -> Result<()> {
+    let json = serde_json::to_string(scores)?;
+    let keys = map_keys(map_md5);
+
+    let mut conn = redis.lock().await;
+
+    redis::pipe()
+        .set_ex(key, json, CACHE_TTL_SECS)
+        .ignore()
+        .sadd(&keys, key)
+        .ignore()
+        .expire(&keys, CACHE_TTL_SECS as i64)
+        .ignore()
+        .query_async::<()>(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// drops every cached board of the map.
+pub async fn invalidate_map(redis: &RedisConnectionManager, map_md5: &str) -> Result<()> {
+    let keys = map_keys(map_md5);
+
+    let mut conn = redis.lock().await;
+
+    let mut cached: Vec<String> = conn.smembers(&keys).await?;
+    cached.push(keys);
+
+    let _: () = conn.del(cached).await?;
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use redis::AsyncCommands;
+
+use crate::infrastructure::redis::RedisConnectionManager;
+
+// refresh_map clears these right away, the ttl is for maps that get submitted without one
+const CACHE_TTL_SECS: u64 = 3600;
+
+/// why a map the client asked for isn't on our side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingMap {
+    Unsubmitted,
+    NeedsUpdate,
+}
+
+impl MissingMap {
+    fn as_str(self) -> &'static str {
+        match self {
+            MissingMap::Unsubmitted => "unsubmitted",
+            MissingMap::NeedsUpdate => "needs_update",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "unsubmitted" => Some(MissingMap::Unsubmitted),
+            "needs_update" => Some(MissingMap::NeedsUpdate),
+            _ => None,
+        }
+    }
+}
+
+fn cache_key(map_md5: &str) -> String {
+    format!("refx:missing_map:{map_md5}")
+}
+
+pub async fn fetch(redis: &RedisConnectionManager, map_md5: &str) -> Result<Option<MissingMap>> {
+    let mut conn = redis.lock().await;
+
+    let cached: Option<String> = conn.get(cache_key(map_md5)).await?;
+
+    Ok(cached.as_deref().and_then(MissingMap::parse))
+}
+
+pub async fn store(
+    redis: &RedisConnectionManager,
+    map_md5: &str,
+    missing: MissingMap,
+) -> Result<()> {
+    let mut conn = redis.lock().await;
+
+    let _: () = conn
+        .set_ex(cache_key(map_md5), missing.as_str(), CACHE_TTL_SECS)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn clear(redis: &RedisConnectionManager, map_md5: &str) -> Result<()> {
+    let mut conn = redis.lock().await;
+
+    let _: () = conn.del(cache_key(map_md5)).await?;
+
+    Ok(())
+}
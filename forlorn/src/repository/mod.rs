@@ -1,10 +1,20 @@
 pub mod achievement;
+pub mod analysis;
 pub mod beatmap;
+pub mod beatmap_cache;
 pub mod clan;
 pub mod error;
 pub mod favourite;
+pub mod fingerprint;
+pub mod lazer_score;
 pub mod leaderboard;
+pub mod leaderboard_cache;
+pub mod missing_map;
+pub mod outbox;
+pub mod pp_queue;
 pub mod rating;
+pub mod replay_view;
 pub mod score;
 pub mod stats;
+pub mod tournament;
 pub mod user;
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    constants::OutboxStatus,
+    infrastructure::database::{DbConnection, DbPoolManager},
+    models::OutboxEvent,
+};
+
+const EVENT_COLUMNS: &str =
+    "id, channel, payload, status, attempts, last_error, next_attempt_at, created_at, delivered_at";
+
+pub async fn insert(conn: &mut DbConnection, channel: &str, payload: &str) -> Result<u64> {
+    let res = sqlx::query("insert into outbox_events (channel, payload) values (?, ?)")
+        .bind(channel)
+        .bind(payload)
+        .execute(conn)
+        .await?;
+
+    Ok(res.last_insert_id())
+}
+
+/// claims the pending events that are due, so other instances skips them
+/// until `claim_secs` has passed.
+pub async fn claim_due(
+    db: &DbPoolManager,
+    claimer: &str,
+    limit: u32,
+    claim_secs: u32,
+) -> Result<Vec<OutboxEvent>> {
+    sqlx::query(
+        "update outbox_events
+         set claimed_by = ?, claimed_until = now() + interval ? second
+         where status = ? and next_attempt_at <= now()
+         and (claimed_until is null or claimed_until < now())
+         order by id
+         limit ?",
+    )
+    .bind(claimer)
+    .bind(claim_secs)
+    .bind(OutboxStatus::Pending.as_i8())
+    .bind(limit)
+    .execute(db.as_ref())
+    .await?;
+
+    let events = sqlx::query_as::<_, OutboxEvent>(&format!(
+        "select {EVENT_COLUMNS} from outbox_events
+         where claimed_by = ? and status = ? and claimed_until >= now()
+         order by id"
+    ))
+    .bind(claimer)
+    .bind(OutboxStatus::Pending.as_i8())
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(events)
+}
+
+pub async fn mark_delivered(db: &DbPoolManager, id: u64) -> Result<()> {
+    sqlx::query(
+        "update outbox_events
+         set status = ?, attempts = attempts + 1, delivered_at = now(),
+         last_error = null, claimed_by = null, claimed_until = null
+         where id = ?",
+    )
+    .bind(OutboxStatus::Delivered.as_i8())
+    .bind(id)
+    .execute(db.as_ref())
+    .await?;
+
+    Ok(())
+}
+
+/// records a failed attempt, either scheduling the next one or giving up.
+pub async fn mark_attempt_failed(
+    db: &DbPoolManager,
+    id: u64,
+    error: &str,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let status = match next_attempt_at {
+        Some(_) => OutboxStatus::Pending,
+        None => OutboxStatus::Failed,
+    };
+
+    sqlx::query(
+        "update outbox_events
+         set status = ?, attempts = attempts + 1, last_error = ?,
+         next_attempt_at = coalesce(?, next_attempt_at),
+         claimed_by = null, claimed_until = null
+         where id = ?",
+    )
+    .bind(status.as_i8())
+    .bind(error.chars().take(512).collect::<String>())
+    .bind(next_attempt_at)
+    .bind(id)
+    .execute(db.as_ref())
+    .await?;
+
+    Ok(())
+}
+
+pub async fn fetch_by_status(
+    db: &DbPoolManager,
+    status: OutboxStatus,
+    limit: u32,
+) -> Result<Vec<OutboxEvent>> {
+    let events = sqlx::query_as::<_, OutboxEvent>(&format!(
+        "select {EVENT_COLUMNS} from outbox_events where status = ? order by id desc limit ?"
+    ))
+    .bind(status.as_i8())
+    .bind(limit)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(events)
+}
+
+/// puts failed events back in the queue, every one of them if `id` is none.
+pub async fn requeue_failed(db: &DbPoolManager, id: Option<u64>) -> Result<u64> {
+    let res = sqlx::query(
+        "update outbox_events
+         set status = ?, attempts = 0, next_attempt_at = now()
+         where status = ? and (? is null or id = ?)",
+    )
+    .bind(OutboxStatus::Pending.as_i8())
+    .bind(OutboxStatus::Failed.as_i8())
+    .bind(id)
+    .bind(id)
+    .execute(db.as_ref())
+    .await?;
+
+    Ok(res.rows_affected())
+}
+
+pub async fn prune_delivered(db: &DbPoolManager, older_than: DateTime<Utc>) -> Result<u64> {
+    let res = sqlx::query("delete from outbox_events where status = ? and delivered_at < ?")
+        .bind(OutboxStatus::Delivered.as_i8())
+        .bind(older_than)
+        .execute(db.as_ref())
+        .await?;
+
+    Ok(res.rows_affected())
+}
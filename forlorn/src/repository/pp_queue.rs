@@ -0,0 +1,58 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::infrastructure::database::{DbConnection, DbPoolManager};
+
+/// marks the score as "pp pending".
+pub async fn insert(conn: &mut DbConnection, score_id: u64) -> Result<()> {
+    sqlx::query("insert ignore into pp_queue (score_id) values (?)")
+        .bind(score_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// (score_id, attempts) of the scores that are due for another try.
+pub async fn fetch_due(db: &DbPoolManager, limit: u32) -> Result<Vec<(u64, i32)>> {
+    let entries = sqlx::query_as::<_, (u64, i32)>(
+        "select score_id, attempts from pp_queue
+         where next_attempt_at <= now()
+         order by next_attempt_at
+         limit ?",
+    )
+    .bind(limit)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(entries)
+}
+
+pub async fn reschedule(
+    db: &DbPoolManager,
+    score_id: u64,
+    error: &str,
+    next_attempt_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        "update pp_queue set attempts = attempts + 1, last_error = ?, next_attempt_at = ?
+         where score_id = ?",
+    )
+    .bind(error.chars().take(512).collect::<String>())
+    .bind(next_attempt_at)
+    .bind(score_id)
+    .execute(db.as_ref())
+    .await?;
+
+    Ok(())
+}
+
+/// returns false if someone else already took care of it.
+pub async fn delete(conn: &mut DbConnection, score_id: u64) -> Result<bool> {
+    let res = sqlx::query("delete from pp_queue where score_id = ?")
+        .bind(score_id)
+        .execute(conn)
+        .await?;
+
+    Ok(res.rows_affected() > 0)
+}
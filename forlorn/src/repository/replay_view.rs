@@ -0,0 +1,68 @@
+use anyhow::Result;
+
+use crate::{
+    constants::SubmissionStatus,
+    infrastructure::{
+        database::{DbConnection, DbPoolManager},
+        redis::RedisConnectionManager,
+    },
+    models::WatchedReplay,
+};
+
+/// true the first time the viewer watches the score within the window.
+pub async fn mark_viewed(
+    redis: &RedisConnectionManager,
+    viewer_id: i32,
+    score_id: u64,
+    window_secs: u64,
+) -> Result<bool> {
+    let mut conn = redis.lock().await;
+
+    let set: Option<String> = redis::cmd("SET")
+        .arg(format!("refx:replay_view:{viewer_id}:{score_id}"))
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(window_secs)
+        .query_async(&mut *conn)
+        .await?;
+
+    Ok(set.is_some())
+}
+
+pub async fn increment(conn: &mut DbConnection, score_id: u64, mode: i32) -> Result<()> {
+    sqlx::query(
+        "insert into replay_views (score_id, mode, views) values (?, ?, 1)
+         on duplicate key update views = views + 1",
+    )
+    .bind(score_id)
+    .bind(mode)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// only replays everyone is allowed to watch.
+pub async fn fetch_most_watched(
+    db: &DbPoolManager,
+    mode: i32,
+    limit: u32,
+) -> Result<Vec<WatchedReplay>> {
+    let replays = sqlx::query_as::<_, WatchedReplay>(
+        "select rv.score_id, rv.views, s.userid, u.name, s.map_md5, s.mods, s.pp, s.acc
+         from replay_views rv
+         inner join scores s on s.id = rv.score_id
+         inner join users u on u.id = s.userid
+         where rv.mode = ? and s.status = ? and u.priv & 1 and not u.private_replays
+         order by rv.views desc, rv.score_id
+         limit ?",
+    )
+    .bind(mode)
+    .bind(SubmissionStatus::Best.as_i32())
+    .bind(limit)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(replays)
+}
@@ -1,20 +1,25 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, mysql::MySqlArguments, query::QueryAs};
 
 use crate::{
-    constants::SubmissionStatus,
-    infrastructure::database::DbPoolManager,
+    constants::{LAZER_ONLINE_CHECKSUM, SubmissionStatus},
+    infrastructure::database::{DbConnection, DbPoolManager},
     models::{Beatmap, Score},
 };
 
+/// `lazer` picks which pool the best comes from, stable & lazer each have their own.
 pub async fn fetch_best(
     db: &DbPoolManager,
     user_id: i32,
     map_md5: &str,
     mode: i32,
+    lazer: bool,
 ) -> Result<Option<Score>> {
     let score = sqlx::query_as::<_, Score>(
         "select * from scores 
          where userid = ? AND map_md5 = ? AND mode = ? AND status = ?
+         and (online_checksum = ?) = ?
          order by pp DESC
          limit 1",
     )
@@ -22,6 +27,8 @@ pub async fn fetch_best(
     .bind(map_md5)
     .bind(mode)
     .bind(SubmissionStatus::Best.as_i32())
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .bind(lazer)
     .fetch_optional(db.as_ref())
     .await?;
 
@@ -47,26 +54,42 @@ pub async fn fetch_by_online_checksum(
         .await
 }
 
-pub async fn update_status(db: &DbPoolManager, score_id: u64, status: i32) -> Result<()> {
+pub async fn update_status(conn: &mut DbConnection, score_id: u64, status: i32) -> Result<()> {
     sqlx::query("update scores set status = ? where id = ?")
         .bind(status)
         .bind(score_id)
-        .execute(db.as_ref())
+        .execute(conn)
         .await?;
 
     Ok(())
 }
 
-pub async fn update_preexisting_personal_best(db: &DbPoolManager, score: &Score) -> Result<()> {
+pub async fn update_pp(conn: &mut DbConnection, score_id: u64, pp: f32) -> Result<()> {
+    sqlx::query("update scores set pp = ? where id = ?")
+        .bind(pp)
+        .bind(score_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_preexisting_personal_best(
+    conn: &mut DbConnection,
+    score: &Score,
+) -> Result<()> {
     sqlx::query(
         "update scores set status = 1 
          where status = 2 and map_md5 = ?
-         and userid = ? and mode = ?",
+         and userid = ? and mode = ?
+         and (online_checksum = ?) = ?",
     )
     .bind(&score.map_md5)
     .bind(score.userid)
     .bind(score.mode)
-    .execute(db.as_ref())
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .bind(score.is_lazer())
+    .execute(conn)
     .await?;
 
     Ok(())
@@ -79,10 +102,12 @@ pub async fn fetch_num_better_scores(db: &DbPoolManager, score: &Score) -> Resul
          inner join users u on u.id = s.userid
          where s.map_md5 = ? and s.mode = ?
          and s.status = 2 and (u.priv & 1) != 0
+         and s.online_checksum != ?
          and s.pp > ?",
     )
     .bind(&score.map_md5)
     .bind(score.mode)
+    .bind(LAZER_ONLINE_CHECKSUM)
     .bind(score.pp)
     .fetch_one(db.as_ref())
     .await?;
@@ -90,7 +115,7 @@ pub async fn fetch_num_better_scores(db: &DbPoolManager, score: &Score) -> Resul
     Ok((num_better_scores + 1) as u32)
 }
 
-pub async fn insert(db: &DbPoolManager, score: &Score, beatmap: &Beatmap) -> Result<u64> {
+pub async fn insert(conn: &mut DbConnection, score: &Score, beatmap: &Beatmap) -> Result<u64> {
     let res = sqlx::query(
         "insert into scores (
          map_md5, map_status, score, xp_gained, pp, acc, max_combo, mods, n300, n100, n50, nmiss, ngeki, nkatu, 
@@ -133,8 +158,151 @@ pub async fn insert(db: &DbPoolManager, score: &Score, beatmap: &Beatmap) -> Res
         .bind(score.uses_hd_remover)
         .bind(score.pinned)
         .bind(score.clock_rate)
-        .execute(db.as_ref())
+        .execute(conn)
         .await?;
 
     Ok(res.last_insert_id())
 }
+
+/// narrows down the scores picked up by a bulk recalculation, `None` matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct ScoreFilter {
+    pub map_md5: Option<String>,
+    pub set_id: Option<i32>,
+    pub mode: Option<i32>,
+    pub user_id: Option<i32>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+const FILTER_CLAUSE: &str = "(? is null or s.map_md5 = ?) and (? is null or m.set_id = ?)
+     and (? is null or s.mode = ?) and (? is null or s.userid = ?)
+     and (? is null or s.play_time >= ?) and (? is null or s.play_time < ?)";
+
+fn bind_filter<'q, O>(
+    query: QueryAs<'q, MySql, O, MySqlArguments>,
+    filter: &'q ScoreFilter,
+) -> QueryAs<'q, MySql, O, MySqlArguments> {
+    query
+        .bind(&filter.map_md5)
+        .bind(&filter.map_md5)
+        .bind(filter.set_id)
+        .bind(filter.set_id)
+        .bind(filter.mode)
+        .bind(filter.mode)
+        .bind(filter.user_id)
+        .bind(filter.user_id)
+        .bind(filter.from)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.to)
+}
+
+/// (userid, mode) pairs with scores matching the filter, ordered so it can be paged through.
+pub async fn fetch_recalc_units(
+    db: &DbPoolManager,
+    filter: &ScoreFilter,
+    after: (i32, i32),
+    limit: u32,
+) -> Result<Vec<(i32, i32)>> {
+    let query = format!(
+        "select distinct s.userid, s.mode from scores s
+         left join maps m on m.md5 = s.map_md5
+         where {FILTER_CLAUSE} and (s.userid, s.mode) > (?, ?)
+         order by s.userid, s.mode
+         limit ?"
+    );
+
+    let units = bind_filter(sqlx::query_as::<_, (i32, i32)>(&query), filter)
+        .bind(after.0)
+        .bind(after.1)
+        .bind(limit)
+        .fetch_all(db.as_ref())
+        .await?;
+
+    Ok(units)
+}
+
+pub async fn fetch_for_recalc(
+    db: &DbPoolManager,
+    filter: &ScoreFilter,
+    user_id: i32,
+    mode: i32,
+) -> Result<Vec<Score>> {
+    let query = format!(
+        "select s.* from scores s
+         left join maps m on m.md5 = s.map_md5
+         where {FILTER_CLAUSE} and s.userid = ? and s.mode = ?
+         order by s.id"
+    );
+
+    let scores = bind_filter(sqlx::query_as::<_, Score>(&query), filter)
+        .bind(user_id)
+        .bind(mode)
+        .fetch_all(db.as_ref())
+        .await?;
+
+    Ok(scores)
+}
+
+/// (id, pp, status, lazer) of every passed score the user has on the map.
+pub async fn fetch_passed_on_map(
+    conn: &mut DbConnection,
+    user_id: i32,
+    map_md5: &str,
+    mode: i32,
+) -> Result<Vec<(u64, f32, i32, bool)>> {
+    let scores = sqlx::query_as::<_, (u64, f32, i32, bool)>(
+        "select id, pp, status, online_checksum = ? from scores
+         where userid = ? and map_md5 = ? and mode = ? and status in (?, ?)
+         order by id",
+    )
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .bind(user_id)
+    .bind(map_md5)
+    .bind(mode)
+    .bind(SubmissionStatus::Submitted.as_i32())
+    .bind(SubmissionStatus::Best.as_i32())
+    .fetch_all(conn)
+    .await?;
+
+    Ok(scores)
+}
+
+/// ids of the passed scores after `after_id`, the ones that can have a replay.
+pub async fn fetch_ids_with_replay(
+    db: &DbPoolManager,
+    after_id: u64,
+    limit: u32,
+) -> Result<Vec<u64>> {
+    let ids = sqlx::query_scalar::<_, u64>(
+        "select id from scores
+         where id > ? and status in (?, ?) and online_checksum != ?
+         order by id
+         limit ?",
+    )
+    .bind(after_id)
+    .bind(SubmissionStatus::Submitted.as_i32())
+    .bind(SubmissionStatus::Best.as_i32())
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .bind(limit)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(ids)
+}
+
+/// maps the user shows up on the leaderboards of.
+pub async fn fetch_ranked_map_md5s(db: &DbPoolManager, user_id: i32) -> Result<Vec<String>> {
+    let maps = sqlx::query_scalar::<_, String>(
+        "select distinct map_md5 from scores
+         where userid = ? and status in (?, ?)",
+    )
+    .bind(user_id)
+    .bind(SubmissionStatus::Submitted.as_i32())
+    .bind(SubmissionStatus::Best.as_i32())
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(maps)
+}
@@ -1,8 +1,14 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use redis::AsyncCommands;
 
 use crate::{
-    infrastructure::{database::DbPoolManager, redis::RedisConnectionManager},
+    constants::LAZER_ONLINE_CHECKSUM,
+    infrastructure::{
+        database::{DbConnection, DbPoolManager},
+        redis::RedisConnectionManager,
+    },
     models::Stats,
 };
 
@@ -29,39 +35,181 @@ pub async fn fetch_by_user_mode(
     Ok(Some(stats))
 }
 
-pub async fn fetch_total_scores(db: &DbPoolManager, stats: &Stats) -> Result<Vec<(f32, f32)>> {
+/// same as `fetch_by_user_mode`, but the row stays locked until the transaction
+/// ends, so two writers can't both start from the same numbers.
+pub async fn fetch_for_update(
+    conn: &mut DbConnection,
+    redis: &RedisConnectionManager,
+    userid: i32,
+    mode: i32,
+) -> Result<Option<Stats>> {
+    let mut stats = match sqlx::query_as::<_, Stats>(
+        "select id, mode, tscore, rscore, pp, plays, playtime, acc, max_combo, total_hits, replay_views, xh_count, x_count, sh_count, s_count, a_count, xp \
+         from stats where id = ? and mode = ? for update"
+    )
+    .bind(userid)
+    .bind(mode)
+    .fetch_optional(conn)
+    .await? {
+        Some(stats) => stats,
+        None => return Ok(None),
+    };
+
+    stats.rank = get_global_rank(redis, &stats).await.unwrap_or(0);
+
+    Ok(Some(stats))
+}
+
+pub async fn fetch_total_scores(conn: &mut DbConnection, stats: &Stats) -> Result<Vec<(f32, f32)>> {
     let scores = sqlx::query_as::<_, (f32, f32)>(
         r#"
         select s.acc, s.pp 
         from scores s 
         right join maps b on s.map_md5 = b.md5 
         where s.status = 2 and s.mode = ? and b.status in (2, 3) and s.userid = ? 
+        and s.online_checksum != ?
         order by s.pp desc 
         limit 100
         "#,
     )
     .bind(stats.mode)
     .bind(stats.id)
-    .fetch_all(db.as_ref())
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .fetch_all(conn)
     .await?;
 
     Ok(scores)
 }
 
-pub async fn fetch_bonus_count(db: &DbPoolManager, stats: &Stats) -> Result<i32> {
+pub async fn fetch_bonus_count(conn: &mut DbConnection, stats: &Stats) -> Result<i32> {
     let count = sqlx::query_scalar::<_, i32>(
         "select count(*) from scores s \
          right join maps b on s.map_md5 = b.md5 \
-         where b.status in (2, 3) and s.status = 2 and s.mode = ? and s.userid = ?",
+         where b.status in (2, 3) and s.status = 2 and s.mode = ? and s.userid = ? \
+         and s.online_checksum != ?",
     )
     .bind(stats.mode)
     .bind(stats.id)
-    .fetch_one(db.as_ref())
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .fetch_one(conn)
     .await?;
 
     Ok(count)
 }
 
+/// (rscore, xh, x, sh, s, a) counted from the ranked bests, for rebuilding the row from scratch.
+pub async fn fetch_ranked_totals(
+    conn: &mut DbConnection,
+    stats: &Stats,
+) -> Result<(u64, u32, u32, u32, u32, u32)> {
+    let (rscore, xh, x, sh, s, a) = sqlx::query_as::<_, (u64, u64, u64, u64, u64, u64)>(
+        "select cast(coalesce(sum(s.score), 0) as unsigned),
+         cast(coalesce(sum(s.grade = 'XH'), 0) as unsigned),
+         cast(coalesce(sum(s.grade = 'X'), 0) as unsigned),
+         cast(coalesce(sum(s.grade = 'SH'), 0) as unsigned),
+         cast(coalesce(sum(s.grade = 'S'), 0) as unsigned),
+         cast(coalesce(sum(s.grade = 'A'), 0) as unsigned)
+         from scores s
+         inner join maps b on s.map_md5 = b.md5
+         where s.status = 2 and s.mode = ? and b.status in (2, 3) and s.userid = ?
+         and s.online_checksum != ?",
+    )
+    .bind(stats.mode)
+    .bind(stats.id)
+    .bind(LAZER_ONLINE_CHECKSUM)
+    .fetch_one(conn)
+    .await?;
+
+    Ok((rscore, xh as u32, x as u32, sh as u32, s as u32, a as u32))
+}
+
+/// (userid, pp, country) of everyone that belongs on the mode's leaderboards.
+pub async fn fetch_leaderboard_entries(
+    db: &DbPoolManager,
+    mode: i32,
+) -> Result<Vec<(i32, u32, String)>> {
+    let entries = sqlx::query_as::<_, (i32, u32, String)>(
+        "select s.id, s.pp, u.country from stats s
+         inner join users u on u.id = s.id
+         where s.mode = ? and s.pp > 0 and (u.priv & 1) != 0",
+    )
+    .bind(mode)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(entries)
+}
+
+/// rebuilds the global & country leaderboards of a mode.
+///
+/// every board is written to a temporary key first and renamed over the old one,
+/// so nobody sees a half empty leaderboard in the meantime. country boards that
+/// nobody belongs to anymore (everyone moved away) are dropped.
+pub async fn reseed_leaderboards(
+    redis: &RedisConnectionManager,
+    mode: i32,
+    entries: &[(i32, u32, String)],
+) -> Result<()> {
+    let mut boards: HashMap<String, Vec<(u32, i32)>> = HashMap::new();
+
+    boards.insert(format!("bancho:leaderboard:{mode}"), Vec::new());
+
+    for (id, pp, country) in entries {
+        boards
+            .entry(format!("bancho:leaderboard:{mode}"))
+            .or_default()
+            .push((*pp, *id));
+        boards
+            .entry(format!("bancho:leaderboard:{mode}:{country}"))
+            .or_default()
+            .push((*pp, *id));
+    }
+
+    let mut conn = redis.lock().await;
+
+    let mut cursor: u64 = 0;
+
+    loop {
+        let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("bancho:leaderboard:{mode}:*"))
+            .arg("COUNT")
+            .arg(1000)
+            .query_async(&mut *conn)
+            .await?;
+
+        for key in keys.iter().filter(|key| !boards.contains_key(*key)) {
+            conn.del::<_, ()>(key).await?;
+        }
+
+        if next == 0 {
+            break;
+        }
+
+        cursor = next;
+    }
+
+    for (key, members) in boards {
+        if members.is_empty() {
+            conn.del::<_, ()>(&key).await?;
+            continue;
+        }
+
+        let tmp = format!("{key}:reseed");
+
+        conn.del::<_, ()>(&tmp).await?;
+
+        for chunk in members.chunks(1000) {
+            conn.zadd_multiple::<_, _, _, ()>(&tmp, chunk).await?;
+        }
+
+        conn.rename::<_, _, ()>(&tmp, &key).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn get_global_rank(redis: &RedisConnectionManager, stats: &Stats) -> Result<i32> {
     let leaderboard = format!("bancho:leaderboard:{}", stats.mode);
     let mut conn = redis.lock().await;
@@ -94,17 +242,42 @@ pub async fn update_rank(
     get_global_rank(redis, stats).await
 }
 
-pub async fn increment_replay_views(db: DbPoolManager, user_id: i32, mode: i32) -> Result<()> {
+pub async fn increment_replay_views(
+    conn: &mut DbConnection,
+    user_id: i32,
+    mode: i32,
+) -> Result<()> {
     sqlx::query("update stats set replay_views = replay_views + 1 where id = ? and mode = ?")
         .bind(user_id)
         .bind(mode)
-        .execute(db.as_ref())
+        .execute(conn)
         .await?;
 
     Ok(())
 }
 
-pub async fn save(db: &DbPoolManager, stats: &Stats) -> Result<()> {
+/// only writes what a recalc changes, so plays landing in the meantime aren't overwritten.
+pub async fn save_ranked(conn: &mut DbConnection, stats: &Stats) -> Result<()> {
+    sqlx::query(
+        "update stats set rscore = ?, pp = ?, acc = ?, xh_count = ?, x_count = ?, sh_count = ?, s_count = ?, a_count = ? where id = ? and mode = ?"
+    )
+    .bind(stats.rscore)
+    .bind(stats.pp)
+    .bind(stats.acc)
+    .bind(stats.xh_count)
+    .bind(stats.x_count)
+    .bind(stats.sh_count)
+    .bind(stats.s_count)
+    .bind(stats.a_count)
+    .bind(stats.id)
+    .bind(stats.mode)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn save(conn: &mut DbConnection, stats: &Stats) -> Result<()> {
     sqlx::query(
         "update stats set tscore = ?, rscore = ?, pp = ?, plays = ?, playtime = ?, acc = ?, max_combo = ?, total_hits = ?, replay_views = ?, xh_count = ?, x_count = ?, sh_count = ?, s_count = ?, a_count = ?, xp = ? where id = ? and mode = ?"
     )
@@ -125,7 +298,7 @@ pub async fn save(db: &DbPoolManager, stats: &Stats) -> Result<()> {
     .bind(stats.xp)
     .bind(stats.id)
     .bind(stats.mode)
-    .execute(db.as_ref())
+    .execute(conn)
     .await?;
 
     Ok(())
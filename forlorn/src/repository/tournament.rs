@@ -0,0 +1,111 @@
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::{
+    dto::v1::tournament::CreateTournament,
+    infrastructure::database::{DbConnection, DbPoolManager},
+    models::{Tournament, TournamentStanding},
+    repository::leaderboard::{bind_tournament, tournament_pool},
+};
+
+pub async fn fetch_by_id(db: &DbPoolManager, tournament_id: u32) -> Result<Option<Tournament>> {
+    let tournament = sqlx::query_as::<_, Tournament>(
+        "select id, name, mods, starts_at, ends_at from tournaments where id = ?",
+    )
+    .bind(tournament_id)
+    .fetch_optional(db.as_ref())
+    .await?;
+
+    Ok(tournament)
+}
+
+/// the running tournament the map is in, the one ending first if there's more.
+pub async fn fetch_running_for_map(
+    db: &DbPoolManager,
+    map_md5: &str,
+) -> Result<Option<Tournament>> {
+    let now = Utc::now();
+
+    let tournament = sqlx::query_as::<_, Tournament>(
+        "select t.id, t.name, t.mods, t.starts_at, t.ends_at from tournaments t
+         inner join tournament_maps tm on tm.tournament_id = t.id
+         where tm.map_md5 = ? and t.starts_at <= ? and t.ends_at > ?
+         order by t.ends_at, t.id
+         limit 1",
+    )
+    .bind(map_md5)
+    .bind(now)
+    .bind(now)
+    .fetch_optional(db.as_ref())
+    .await?;
+
+    Ok(tournament)
+}
+
+pub async fn fetch_map_md5s(db: &DbPoolManager, tournament_id: u32) -> Result<Vec<String>> {
+    let maps = sqlx::query_scalar::<_, String>(
+        "select map_md5 from tournament_maps where tournament_id = ? order by map_md5",
+    )
+    .bind(tournament_id)
+    .fetch_all(db.as_ref())
+    .await?;
+
+    Ok(maps)
+}
+
+pub async fn insert(conn: &mut DbConnection, tournament: &CreateTournament) -> Result<u32> {
+    let result =
+        sqlx::query("insert into tournaments (name, mods, starts_at, ends_at) values (?, ?, ?, ?)")
+            .bind(&tournament.name)
+            .bind(tournament.mods)
+            .bind(tournament.starts_at)
+            .bind(tournament.ends_at)
+            .execute(conn)
+            .await?;
+
+    Ok(result.last_insert_id() as u32)
+}
+
+pub async fn insert_map(conn: &mut DbConnection, tournament_id: u32, map_md5: &str) -> Result<()> {
+    sqlx::query("insert ignore into tournament_maps (tournament_id, map_md5) values (?, ?)")
+        .bind(tournament_id)
+        .bind(map_md5)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// players ranked by their best scores across the whole pool added up.
+/// `order_by` has to be either `pp` or `score`.
+pub async fn fetch_standings(
+    db: &DbPoolManager,
+    tournament: &Tournament,
+    mode: i32,
+    order_by: &str,
+    limit: u32,
+) -> Result<Vec<TournamentStanding>> {
+    let query = format!(
+        "select u.id as userid, u.name, count(*) as maps_played,
+                cast(sum(s.pp) as double) as pp, cast(sum(s.score) as signed) as score
+         from scores s
+         inner join users u on u.id = s.userid
+         inner join tournament_maps tm on tm.map_md5 = s.map_md5 and tm.tournament_id = ?
+         where s.mode = ? and u.priv & 1 and {}
+         group by u.id, u.name
+         order by {order_by} desc, u.id
+         limit ?",
+        tournament_pool(order_by)
+    );
+
+    let query = sqlx::query_as::<_, TournamentStanding>(&query)
+        .bind(tournament.id)
+        .bind(mode);
+
+    let standings = bind_tournament(query, tournament)
+        .bind(limit)
+        .fetch_all(db.as_ref())
+        .await?;
+
+    Ok(standings)
+}
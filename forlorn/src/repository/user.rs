@@ -3,6 +3,7 @@ use std::time::SystemTime;
 use anyhow::Result;
 
 use crate::{
+    constants::LAZER_ONLINE_CHECKSUM,
     infrastructure::database::DbPoolManager,
     models::{Score, User},
 };
@@ -12,7 +13,7 @@ pub async fn fetch_by_name(db: &DbPoolManager, username: &str) -> Result<Option<
         "select id, name, safe_name, priv as privilege, pw_bcrypt, country, silence_end, donor_end, 
                 creation_time, latest_activity, clan_id, clan_priv, preferred_mode, 
                 play_style, custom_badge_name, custom_badge_icon, userpage_content, 
-                api_key, whitelist, preferred_metric from users where name = ?"
+                api_key, whitelist, preferred_metric, private_replays, clan_leaderboard, tournament_leaderboard from users where name = ?"
     )
         .bind(username)
         .fetch_optional(db.as_ref())
@@ -26,7 +27,7 @@ pub async fn fetch_by_id(db: &DbPoolManager, id: &i32) -> Result<Option<User>> {
         "select id, name, safe_name, priv as privilege, pw_bcrypt, country, silence_end, donor_end, 
                 creation_time, latest_activity, clan_id, clan_priv, preferred_mode, 
                 play_style, custom_badge_name, custom_badge_icon, userpage_content, 
-                api_key, whitelist, preferred_metric from users where id = ?"
+                api_key, whitelist, preferred_metric, private_replays, clan_leaderboard, tournament_leaderboard from users where id = ?"
     )
         .bind(id)
         .fetch_optional(db.as_ref())
@@ -44,10 +45,12 @@ pub async fn fetch_prev_n1(
             inner join scores s on u.id = s.userid 
             where s.map_md5 = ? and s.mode = ? 
             and s.status = 2 and u.priv & 1 
+            and s.online_checksum != ?
             order by s.pp desc limit 1",
     )
     .bind(&score.map_md5)
     .bind(score.mode)
+    .bind(LAZER_ONLINE_CHECKSUM)
     .fetch_optional(db.as_ref())
     .await?;
 
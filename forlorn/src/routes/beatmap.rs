@@ -53,6 +53,7 @@ pub async fn get_beatmap_info(
             // NOTE: izin brok, kita cuman ambil std doang soalnya
             //       gabisa ngambil mode realtime dari cho
             0,
+            false,
         )
         .await
         .unwrap_or_default();
@@ -55,7 +55,7 @@ pub async fn get_lastfm(
 
         tokio::spawn(async move {
             let _ = restrict::restrict(
-                &state.redis,
+                &state.outbox,
                 user.id,
                 &format!("hq!osu files found ({})", explanations),
             )
@@ -74,7 +74,7 @@ pub async fn get_lastfm(
 
         tokio::spawn(async move {
             let _ = restrict::restrict(
-                &state.redis,
+                &state.outbox,
                 user.id,
                 &format!("invalid cheat values ({})", explanations),
             )
@@ -87,7 +87,7 @@ pub async fn get_lastfm(
     // since its possible that they doesn't even remember those multi account times
     // if flags.contains(LastFmFlags::REGISTRY_EDITS) {
     //     tokio::spawn(async move {
-    //         let _ = restrict::restrict(&state.redis, user.id, "hq!osu relife registry edits found").await;
+    //         let _ = restrict::restrict(&state.outbox, user.id, "hq!osu relife registry edits found").await;
     //     });
     //     return (StatusCode::OK, b"-3").into_response();
     // }
@@ -10,10 +10,13 @@ use crate::{
     constants::{LeaderboardType, RankedStatus},
     dto::leaderboard::GetScores,
     models::{PersonalBest, User},
-    repository,
+    repository::{self, missing_map::MissingMap},
     state::AppState,
-    usecases::password::verify_password,
-    utils::{build_display_name, build_empty_leaderboard, build_leaderboard_response},
+    usecases::{
+        leaderboard::{fetch_leaderboard_scores, leaderboard_filter, resolve_leaderboard},
+        password::verify_password,
+    },
+    utils::{build_empty_leaderboard, build_leaderboard_response},
 };
 
 async fn authenticate_user(
@@ -34,30 +37,32 @@ async fn authenticate_user(
     }
 }
 
+fn missing_map_response(missing: MissingMap) -> Response {
+    match missing {
+        MissingMap::Unsubmitted => (StatusCode::OK, b"-1|false").into_response(),
+        MissingMap::NeedsUpdate => (StatusCode::OK, b"1|false").into_response(),
+    }
+}
+
 async fn handle_missing_beatmap(state: &AppState, leaderboard: &GetScores) -> Response {
     let has_set_id = leaderboard.map_set_id > 0;
 
-    if !has_set_id {
-        state.unsubmitted_maps.insert(leaderboard.map_md5.clone());
-
-        return (StatusCode::OK, b"-1|false").into_response();
-    }
-
-    let map_exists = repository::beatmap::fetch_by_filename(&state.db, &leaderboard.map_filename)
-        .await
-        .ok()
-        .flatten()
-        .is_some();
+    let missing = if !has_set_id {
+        MissingMap::Unsubmitted
+    } else {
+        let map_exists =
+            repository::beatmap::fetch_by_filename(&state.db, &leaderboard.map_filename)
+                .await
+                .ok()
+                .flatten()
+                .is_some();
 
-    if map_exists {
-        state.needs_update_maps.insert(leaderboard.map_md5.clone());
+        if map_exists { MissingMap::NeedsUpdate } else { MissingMap::Unsubmitted }
+    };
 
-        (StatusCode::OK, b"1|false").into_response()
-    } else {
-        state.unsubmitted_maps.insert(leaderboard.map_md5.clone());
+    let _ = repository::missing_map::store(&state.redis, &leaderboard.map_md5, missing).await;
 
-        (StatusCode::OK, b"-1|false").into_response()
-    }
+    missing_map_response(missing)
 }
 
 pub async fn get_scores(
@@ -90,11 +95,10 @@ pub async fn get_scores(
 
     let now = Instant::now();
 
-    if state.unsubmitted_maps.contains(&leaderboard.map_md5) {
-        return (StatusCode::OK, b"-1|false").into_response();
-    }
-    if state.needs_update_maps.contains(&leaderboard.map_md5) {
-        return (StatusCode::OK, b"1|false").into_response();
+    if let Ok(Some(missing)) =
+        repository::missing_map::fetch(&state.redis, &leaderboard.map_md5).await
+    {
+        return missing_map_response(missing);
     }
 
     let user =
@@ -105,8 +109,6 @@ pub async fn get_scores(
 
     let mode = leaderboard.mode();
 
-    let leaderboard_type = LeaderboardType::from_i32(leaderboard.leaderboard_type);
-
     let beatmap =
         match repository::beatmap::fetch_by_md5(&state.config, &state.db, &leaderboard.map_md5)
             .await
@@ -136,30 +138,29 @@ pub async fn get_scores(
             .into_response();
     }
 
+    let (leaderboard_type, tournament) = resolve_leaderboard(&state.db, &leaderboard, &user).await;
+
     let friend_ids = if leaderboard_type == LeaderboardType::Friends {
         let mut friends = repository::user::fetch_friend_ids(&state.db, user.id)
             .await
             .unwrap_or_default();
 
         friends.push(user.id);
-        Some(friends)
+        friends
     } else {
-        None
+        Vec::new()
     };
 
-    let scores = match repository::leaderboard::fetch_leaderboard_scores(
-        &state.db,
-        &leaderboard.map_md5,
-        mode.as_i32(),
-        user.id,
+    let filter = leaderboard_filter(
+        &state.config,
+        &leaderboard,
+        &user,
         leaderboard_type,
-        Some(leaderboard.mods),
-        Some(&user.country),
-        friend_ids.as_deref(),
-        user.preferred_metric(),
-        leaderboard.is_refx(),
-    )
-    .await
+        tournament.as_ref(),
+        &friend_ids,
+    );
+
+    let scores = match fetch_leaderboard_scores(&state, &filter, &user, leaderboard.is_refx()).await
     {
         Ok(s) => s,
         Err(e) => return (StatusCode::OK, e.to_string().into_bytes()).into_response(),
@@ -168,10 +169,7 @@ pub async fn get_scores(
     let personal_best = if !scores.is_empty() {
         match repository::leaderboard::fetch_personal_best_score(
             &state.db,
-            &leaderboard.map_md5,
-            mode.as_i32(),
-            user.id,
-            user.preferred_metric(),
+            &filter,
             leaderboard.is_refx(),
         )
         .await
@@ -179,15 +177,12 @@ pub async fn get_scores(
             Ok(Some(mut pb)) => {
                 let rank = repository::leaderboard::fetch_personal_best_rank(
                     &state.db,
-                    &leaderboard.map_md5,
-                    mode.as_i32(),
+                    &filter,
                     pb.preferred_metric,
-                    user.preferred_metric(),
                 )
                 .await
                 .unwrap_or(0);
 
-                pb.name = build_display_name(&user, &state).await;
                 pb.userid = user.id;
 
                 Some(PersonalBest { score: pb, rank })
@@ -8,8 +8,14 @@ use axum::{
 };
 
 use crate::{
-    dto::replay::GetReplay, models::User, repository, state::AppState,
-    usecases::password::verify_password,
+    dto::replay::GetReplay,
+    models::User,
+    repository,
+    state::AppState,
+    usecases::{
+        password::verify_password,
+        replay::{fetch_viewable_replay, record_replay_view},
+    },
 };
 
 async fn authenticate_user(
@@ -36,24 +42,33 @@ pub async fn get_replay(
 ) -> impl IntoResponse {
     let now = Instant::now();
 
-    let score = match repository::score::fetch_by_id(&state.db, replay.score_id).await {
-        Ok(Some(s)) => s,
+    let Ok(user) = authenticate_user(&state, &replay.password_md5, &replay.username).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let score = match fetch_viewable_replay(&state.db, Some(&user), replay.score_id).await {
+        Ok(Some((score, _))) => score,
         _ => return StatusCode::NOT_FOUND.into_response(),
     };
 
-    let user = match authenticate_user(&state, &replay.password_md5, &replay.username).await {
-        Ok(user) => user,
-        Err(response) => return response,
+    let replay_data = match state.storage.load_replay(replay.score_id).await {
+        Ok(replay) => replay,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
     };
 
-    if user.id != score.userid {
+    {
+        let state = state.clone();
+        let viewer_id = user.id;
         tokio::spawn(async move {
-            let _ = repository::stats::increment_replay_views(
-                state.db.clone(),
-                score.userid,
-                score.mode,
-            )
-            .await;
+            match record_replay_view(&state.db, &state.redis, viewer_id, &score).await {
+                Ok(true) => {
+                    let _ = state.metrics.incr("replay.viewed", ["status:counted"]);
+                },
+                Ok(false) => {
+                    let _ = state.metrics.incr("replay.viewed", ["status:deduped"]);
+                },
+                Err(e) => tracing::warn!("failed to count a view of score {}: {e}", score.id),
+            }
         });
     }
 
@@ -67,8 +82,5 @@ pub async fn get_replay(
 
     tracing::info!("Replay served to {} in {}ms.", user.name, done.as_millis());
 
-    match state.storage.load_replay(replay.score_id).await {
-        Ok(replay) => Bytes::from(replay).into_response(),
-        Err(_) => StatusCode::NOT_FOUND.into_response(),
-    }
+    Bytes::from(replay_data).into_response()
 }
@@ -12,25 +12,33 @@ use axum::{
 use webhook::Webhook;
 
 use crate::{
-    constants::{Grade, REFX_AUTH_HASH, REFX_CURRENT_CLIENT_HASH, RankedStatus, SubmissionStatus},
+    constants::{REFX_AUTH_HASH, REFX_CURRENT_CLIENT_HASH, RankedStatus, SubmissionStatus},
     dto::submission::{ScoreHeader, ScoreSubmission},
     infrastructure::redis::publish::{announce, notify, refresh_stats, restrict, score},
     models::{Score, User},
+    replay::Fingerprint,
     repository,
     state::AppState,
     usecases::{
-        beatmap::{ensure_osu_file, increment_playcount},
+        analysis::{analyse_score, review_webhook},
+        beatmap::{ensure_osu_file, fetch_osu_file, fill_private_map, increment_playcount},
+        leaderboard::invalidate_map,
         password::verify_password,
+        replay::{decode_and_verify_replay, find_duplicate_replay, verify_clock_rate},
         score::{
-            calculate_accuracy, calculate_performance, calculate_placement, calculate_status,
-            calculate_xp, consume_cheat_values, decrypt_score_data, first_place_webhook,
-            update_any_preexisting_personal_best, validate_cheat_values,
+            calculate_accuracy, calculate_online_checksum, calculate_placement,
+            calculate_score_performance, calculate_status, calculate_xp, consume_cheat_values,
+            decrypt_score_data, first_place_webhook, update_any_preexisting_personal_best,
+            validate_cheat_values,
         },
-        stats::{get_computed_playtime, recalculate},
+        stats::apply_submission,
+        validation::{ScoreViolation, handle_violations, validate_hit_counts},
     },
     utils::{build_submission, build_submission_charts},
 };
 
+const MIN_REPLAY_SIZE: usize = 24;
+
 async fn authenticate_user(
     state: &AppState,
     password_md5: &str,
@@ -108,13 +116,13 @@ pub async fn submit_score(
         Err(response) => return response,
     };
 
-    let (score_data, osu_path_md5) = match decrypt_score_data(
+    let (score_data, client_hash, osu_path_md5) = match decrypt_score_data(
         &submission.score_data_b64,
         &submission.client_hash_b64,
         &submission.iv_b64,
         &submission.osu_version,
     ) {
-        Ok((v, c)) => (v, c),
+        Ok(decrypted) => decrypted,
         Err(_) => {
             tracing::warn!("decrypt_score_data failed");
             return (StatusCode::OK, b"error: no").into_response();
@@ -140,27 +148,22 @@ pub async fn submit_score(
     //       but for extra safety, maybe i should restrict them too?
     //       since they most likely spoofed `GameBase.ClientHash`.
 
-        if submission.refx() && osu_path_md5 != REFX_CURRENT_CLIENT_HASH {
-            let _ = state
-                .metrics
-                .incr("score.client_hash_flagged", ["status:ok"]);
+    if submission.refx() && osu_path_md5 != REFX_CURRENT_CLIENT_HASH {
+        let _ = state
+            .metrics
+            .incr("score.client_hash_flagged", ["status:ok"]);
 
-            tracing::warn!(
-                "{} submitted a score in outdated/modified re;fx client! ({} != {})",
-                user.name(),
-                osu_path_md5,
-                REFX_CURRENT_CLIENT_HASH,
-            );
+        tracing::warn!(
+            "{} submitted a score in outdated/modified re;fx client! ({} != {})",
+            user.name(),
+            osu_path_md5,
+            REFX_CURRENT_CLIENT_HASH,
+        );
 
-            {
-                let r = state.redis.clone();
-                tokio::spawn(async move {
-                    let _ = notify::notify(&r, user.id, "Please update your client!").await;
-                });
-            }
+        let _ = notify::notify(&state.outbox, user.id, "Please update your client!").await;
 
-            return (StatusCode::OK, b"error: no").into_response();
-        }
+        return (StatusCode::OK, b"error: no").into_response();
+    }
 
     // same as above
     if submission.refx() && submission.auth_hash() != REFX_AUTH_HASH {
@@ -173,12 +176,7 @@ pub async fn submit_score(
             REFX_AUTH_HASH,
         );
 
-        {
-            let r = state.redis.clone();
-            tokio::spawn(async move {
-                let _ = notify::notify(&r, user.id, "Please update your client!").await;
-            });
-        }
+        let _ = notify::notify(&state.outbox, user.id, "Please update your client!").await;
 
         return (StatusCode::OK, b"error: no").into_response();
     }
@@ -188,21 +186,17 @@ pub async fn submit_score(
         // trying to spoof the client hash
         // since there's no `refx` flag
 
-        {
-            let r = state.redis.clone();
-            tokio::spawn(async move {
-                let _ = restrict::restrict(
-                    &r,
-                    user.id,
-                    &format!(
-                        "Trying to spoof the client hash ({osu_path_md5} == {REFX_CURRENT_CLIENT_HASH})"
-                    ),
-                ).await;
-            });
-        }
+        let _ = restrict::restrict(
+            &state.outbox,
+            user.id,
+            &format!(
+                "Trying to spoof the client hash ({osu_path_md5} == {REFX_CURRENT_CLIENT_HASH})"
+            ),
+        )
+        .await;
     }
 
-    let beatmap =
+    let mut beatmap =
         match repository::beatmap::fetch_by_md5(&state.config, &state.db, &score_header.map_md5)
             .await
         {
@@ -275,25 +269,79 @@ pub async fn submit_score(
             .metrics
             .incr("score.mods_conflict", [format!("mods:{}", mods_str)]);
 
-        {
-            let r = state.redis.clone();
-            let user_id = user.id;
-            let mods_str = mods_str.clone();
-            tokio::spawn(async move {
-                let _ = restrict::restrict(
-                    &r,
-                    user_id,
-                    &format!("illegal mod combination ({})", mods_str),
-                )
-                .await;
-            });
-        }
+        let _ = restrict::restrict(
+            &state.outbox,
+            user.id,
+            &format!("illegal mod combination ({})", mods_str),
+        )
+        .await;
 
         tracing::warn!("{} submitted conflicting mods: {}", user.name(), mods_str);
 
         return (StatusCode::OK, b"error: no").into_response();
     }
 
+    let replay = if score.passed && submission.replay_file.len() >= MIN_REPLAY_SIZE {
+        match decode_and_verify_replay(
+            &submission.replay_file,
+            &score,
+            &beatmap,
+            submission.score_time,
+        ) {
+            Ok(replay) => Some(replay),
+            Err(violation) => {
+                let _ = state.metrics.incr(violation.metric(), ["status:ok"]);
+
+                tracing::warn!(
+                    "[{}] {} submitted an inconsistent replay: {}",
+                    score.mode().as_str(),
+                    user.name(),
+                    violation.reason()
+                );
+
+                let _ = restrict::restrict(&state.outbox, user.id, &violation.reason()).await;
+
+                return (StatusCode::OK, b"error: no").into_response();
+            },
+        }
+    } else {
+        None
+    };
+
+    if let Some(replay) = &replay
+        && let Err(violation) = verify_clock_rate(replay, &score)
+    {
+        let _ = state.metrics.incr(violation.metric(), ["status:ok"]);
+
+        let webhook = Webhook::new(&state.config.webhook.debug).content(format!(
+            "[{}] {} Timewarp? ({}) [tw={}|twval={}|rate={}|mods={}]",
+            score.mode().as_str(),
+            user.name(),
+            violation.reason(),
+            score.uses_timewarp,
+            score.timewarp_value,
+            score.clock_rate,
+            score.mods().as_str(score.clock_rate())
+        ));
+
+        tracing::warn!(
+            "[{}] {} submitted a replay with a mismatched clock rate: {}",
+            score.mode().as_str(),
+            user.name(),
+            violation.reason()
+        );
+
+        tokio::spawn(async move {
+            let _ = webhook.post().await;
+        });
+
+        // cheat modes are allowed to timewarp, they just have to be honest about it.
+        // the cheat value checks above already handles the allowed ranges.
+        if !score.mode().cheat() {
+            let _ = restrict::restrict(&state.outbox, user.id, &violation.reason()).await;
+        }
+    }
+
     match ensure_osu_file(&state.config.omajinai, &beatmap).await {
         Ok(true) => {},
         _ => {
@@ -302,6 +350,13 @@ pub async fn submit_score(
         },
     }
 
+    if let Err(e) = fill_private_map(&state.config.omajinai, &state.db, &mut beatmap).await {
+        tracing::warn!(
+            "failed to fill private beatmap {} from its .osu: {e}",
+            beatmap.id
+        );
+    }
+
     let _submission_lock_ = match state
         .score_locks
         .lock(
@@ -336,54 +391,116 @@ pub async fn submit_score(
             return (StatusCode::OK, b"error: no").into_response();
         }
 
-        (score.pp, score.stars, score.hypothetical_pp) = calculate_performance(
-            &state.config.omajinai,
-            beatmap.id,
-            score.mode,
-            score.mods,
-            score.max_combo,
-            score.acc,
-            score.nmiss,
-            score.score,
-            score.clock_rate,
-            Some(score.n300),
-            Some(score.n100),
-            Some(score.n50),
-            Some(score.ngeki),
-            Some(score.nkatu),
-        )
-        .await;
+        // after the duplicate check, so a retried score isn't flagged twice
+        let mut violations = Vec::new();
+
+        // the checksum is only ever used as a key otherwise, so an edited
+        // score string that still decrypts would go through untouched.
+        let expected_checksum = calculate_online_checksum(
+            &score,
+            &score_header.username,
+            &submission.osu_version,
+            &client_hash,
+            submission.storyboard_md5.as_deref().unwrap_or_default(),
+        );
 
-        if score.pp.round() == 2112.0 || score.pp.round() == 727.0 {
-            // And this is the part
-            // Where our whole lives collide
-            // The stars themselves fell
-            // Like we did that night
-            // Though it felt like the universe knew
-            // A pack of friends who couldn't hold their laughter
-            // They chose to be painfully obvious in front of us
-            // Slightly unaware or in denial of the dangers ahead
-            // We thrust our weary hearts into each other's arms
-            // Content and comfortable
-            // For years to come
-            let r = state.redis.clone();
-            tokio::spawn(async move {
-                // She said to me
-                // And I said to her
-                // To hold back each other's true fate
-                // Is not of our nature
-                let _ = notify::notify(&r, user.id, "Let's be mature").await;
-                // Maybe you weren't made for me
-                // Nor I for you
-                // But I'd be damn lying
-                // If I think that that's true
+        if score.online_checksum != expected_checksum {
+            violations.push(ScoreViolation::ChecksumMismatch {
+                submitted: score.online_checksum.clone(),
+                expected: expected_checksum,
             });
         }
 
         if score.passed {
-            if let Ok(Some(prev_best)) = calculate_status(&state.db, &mut score).await {
-                let _ = repository::score::update_status(&state.db, prev_best.id, prev_best.status)
-                    .await;
+            let osu = fetch_osu_file(&state.config.omajinai, &beatmap).await.ok();
+            violations.extend(validate_hit_counts(&score, &beatmap, osu.as_ref()));
+        }
+
+        if handle_violations(&state, &user, &score, &beatmap, &violations).await {
+            return (StatusCode::OK, b"error: no").into_response();
+        }
+
+        // if no calculator is up, the score goes in with 0pp (so it can't demote anything)
+        // and the pp queue fixes it up once it's back, instead of failing the submission.
+        let pp_pending =
+            match calculate_score_performance(&state.performance, &score, &beatmap).await {
+                Ok(result) => {
+                    (score.pp, score.stars, score.hypothetical_pp) = result;
+                    false
+                },
+                Err(e) => {
+                    let _ = state.metrics.incr("score.pp_pending", ["status:ok"]);
+
+                    tracing::warn!(
+                        "pp calculation failed for {} on {}, deferring it: {e}",
+                        user.name(),
+                        beatmap.id,
+                    );
+
+                    score.passed
+                },
+            };
+
+        // a stolen replay gets restricted in the same transaction the score goes in
+        let fingerprint = replay
+            .as_ref()
+            .and_then(|r| Fingerprint::from_replay(r, score.mode()));
+
+        let duplicate_of = match &fingerprint {
+            Some(fingerprint) => {
+                match find_duplicate_replay(&state.db, &score, fingerprint).await {
+                    Ok(duplicate_of) => duplicate_of,
+                    Err(e) => {
+                        tracing::warn!("duplicate replay lookup failed for {}: {e}", user.name());
+                        None
+                    },
+                }
+            },
+            None => None,
+        };
+
+        if let Some(original_id) = duplicate_of {
+            let _ = state.metrics.incr("replay.duplicate", ["status:ok"]);
+
+            tracing::warn!(
+                "[{}] {} submitted a duplicate replay (matches score {})",
+                score.mode().as_str(),
+                user.name(),
+                original_id,
+            );
+        }
+
+        // everything that touches the scores & stats goes through one transaction,
+        // so a failure halfway doesn't leave the previous best demoted without a new one.
+        let mut tx = match state.db.begin().await {
+            Ok(tx) => tx,
+            Err(e) => {
+                tracing::error!("failed to begin submission transaction: {e}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
+            },
+        };
+
+        let mut prev_best = None;
+
+        if score.passed {
+            prev_best = match calculate_status(&state.db, &mut score).await {
+                Ok(prev_best) => prev_best,
+                Err(e) => {
+                    tracing::error!("calculate_status failed for user {}: {e}", user.name());
+                    return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
+                },
+            };
+
+            if let Some(prev_best) = &prev_best
+                && repository::score::update_status(&mut tx, prev_best.id, prev_best.status)
+                    .await
+                    .is_err()
+            {
+                tracing::error!(
+                    "previous best status update failed for user: {}",
+                    user.name()
+                );
+                return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
             }
 
             if beatmap.status != RankedStatus::Pending.as_i32() {
@@ -400,34 +517,28 @@ pub async fn submit_score(
 
         score.xp = calculate_xp(&score, &beatmap);
 
-        let _ = state.metrics.incr("score.submitted", ["status:all"]);
+        let mut first_place = None;
 
         if score.status == SubmissionStatus::Best.as_i32() {
-            let _ = state.metrics.incr("score.submitted", ["status:best"]);
-
             if beatmap.has_leaderboard() && score.rank == 1 && !user.restricted() {
                 let prev_holder = repository::user::fetch_prev_n1(&state.db, &score)
                     .await
                     .ok()
                     .flatten();
 
-                let webhook = first_place_webhook(
-                    &user,
-                    &score,
-                    &beatmap,
-                    &state.config.webhook.score,
-                    prev_holder,
-                );
-
-                tokio::spawn(async move {
-                    let _ = webhook.post().await;
-                });
+                first_place = Some(prev_holder);
             }
 
-            update_any_preexisting_personal_best(&state.db, &score).await;
+            if update_any_preexisting_personal_best(&mut tx, &score)
+                .await
+                .is_err()
+            {
+                tracing::error!("personal best update failed for user: {}", user.name());
+                return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
+            }
         }
 
-        score.id = match repository::score::insert(&state.db, &score, &beatmap).await {
+        score.id = match repository::score::insert(&mut tx, &score, &beatmap).await {
             Ok(id) => id,
             _ => {
                 tracing::error!("score insert failed for user: {}", user.name());
@@ -435,118 +546,116 @@ pub async fn submit_score(
             },
         };
 
-        if score.passed {
-            if score.rank == 1
-                && beatmap.has_leaderboard()
-                && !user.restricted()
-                && score.status == SubmissionStatus::Best.as_i32()
+        if pp_pending
+            && repository::pp_queue::insert(&mut tx, score.id)
+                .await
+                .is_err()
+        {
+            tracing::error!("failed to queue pp calculation for score {}", score.id);
+            return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
+        }
+
+        // update player & beatmap stats
+        let mut stats =
+            match repository::stats::fetch_for_update(&mut tx, &state.redis, user.id, score.mode)
+                .await
             {
-                let r = state.redis.clone();
-                tokio::spawn(async move {
-                    let _ = announce::announce(&r, score.id).await;
-                });
-            }
+                Ok(Some(stats)) => stats,
+                _ => {
+                    tracing::error!(
+                        "stats fetch failed for user {} mode {}",
+                        user.id,
+                        score.mode
+                    );
+                    return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
+                },
+            };
 
-            const MIN_REPLAY_SIZE: usize = 24;
+        let prev_stats = stats.clone();
 
-            if submission.replay_file.len() >= MIN_REPLAY_SIZE {
-                let mut full_replay = submission.replay_file.clone();
+        let ranked_best =
+            match apply_submission(&mut tx, &mut stats, &score, &beatmap, prev_best.as_ref()).await
+            {
+                Ok(ranked_best) => ranked_best,
+                Err(e) => {
+                    tracing::error!("stats update failed for user {}: {e}", user.name());
+                    return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
+                },
+            };
+
+        // the events are written along with the score, so a crash
+        // right after the commit can't lose them.
+        let events = async {
+            if let Some(original_id) = duplicate_of {
+                restrict::restrict(
+                    &mut *tx,
+                    user.id,
+                    &format!("stolen replay (score {} matches {original_id})", score.id),
+                )
+                .await?;
+            }
 
-                if !submission.lazer_data.is_empty() && submission.refx() {
-                    full_replay.extend_from_slice(&submission.lazer_data);
-                }
+            if score.pp.round() == 2112.0 || score.pp.round() == 727.0 {
+                // And this is the part
+                // Where our whole lives collide
+                // The stars themselves fell
+                // Like we did that night
+                // Though it felt like the universe knew
+                // A pack of friends who couldn't hold their laughter
+                // They chose to be painfully obvious in front of us
+                // Slightly unaware or in denial of the dangers ahead
+                // We thrust our weary hearts into each other's arms
+                // Content and comfortable
+                // For years to come
 
-                let _ = state.storage.save_replay(score.id, &full_replay).await;
-            } else {
-                let r = state.redis.clone();
-                tokio::spawn(async move {
-                    let _ = restrict::restrict(&r, user.id, "score submitter?").await;
-                });
+                // She said to me
+                // And I said to her
+                // To hold back each other's true fate
+                // Is not of our nature
+                notify::notify(&mut *tx, user.id, "Let's be mature").await?;
+                // Maybe you weren't made for me
+                // Nor I for you
+                // But I'd be damn lying
+                // If I think that that's true
             }
 
-            if let (true, Some(threshold)) = score.check_pp_cap(&user)
-                && beatmap.awards_ranked_pp()
-            {
-                let _ = state
-                    .metrics
-                    .incr("score.exceeds_pp_cap_threshold", ["status:ok"]);
+            if score.passed {
+                if score.rank == 1
+                    && beatmap.has_leaderboard()
+                    && !user.restricted()
+                    && score.status == SubmissionStatus::Best.as_i32()
+                {
+                    announce::announce(&mut *tx, score.id).await?;
+                }
+
+                if submission.replay_file.len() < MIN_REPLAY_SIZE {
+                    restrict::restrict(&mut *tx, user.id, "score submitter?").await?;
+                }
 
+                if let (true, Some(threshold)) = score.check_pp_cap(&user)
+                    && beatmap.awards_ranked_pp()
                 {
-                    let r = state.redis.clone();
-                    let _ = restrict::restrict(
-                        &r,
+                    let _ = state
+                        .metrics
+                        .incr("score.exceeds_pp_cap_threshold", ["status:ok"]);
+
+                    restrict::restrict(
+                        &mut *tx,
                         user.id,
                         &format!("suspicious pp gain ({}pp > {threshold})", score.pp.round(),),
                     )
-                    .await;
+                    .await?;
                 }
             }
-        }
 
-        // update player & beatmap stats
-        let mut stats = match repository::stats::fetch_by_user_mode(
-            &state.db,
-            &state.redis,
-            user.id,
-            score.mode,
-        )
-        .await
-        {
-            Ok(Some(stats)) => stats,
-            _ => {
-                tracing::error!(
-                    "stats fetch failed for user {} mode {}",
+            if pp_pending {
+                notify::notify(
+                    &mut *tx,
                     user.id,
-                    score.mode
-                );
-                return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
-            },
-        };
-
-        let prev_stats = stats.clone();
-
-        stats.playtime += get_computed_playtime(&score, &beatmap);
-        stats.plays += 1;
-        stats.tscore += score.score as u64;
-        stats.total_hits += score.total_hits();
-        stats.xp += score.xp.round() as i32;
-
-        if score.passed && beatmap.has_leaderboard() {
-            if score.max_combo as u32 > stats.max_combo {
-                stats.max_combo = score.max_combo as u32;
-            }
-
-            if beatmap.awards_ranked_pp() && score.status == SubmissionStatus::Best.as_i32() {
-                let prev_best =
-                    repository::score::fetch_best(&state.db, user.id, &beatmap.md5, score.mode)
-                        .await
-                        .ok()
-                        .flatten();
-
-                let mut additional_rscore = score.score;
-                if let Some(ref pb) = prev_best {
-                    additional_rscore -= pb.score;
-
-                    if score.grade() != pb.grade() {
-                        if score.grade() >= Grade::A {
-                            stats.increment_grade(score.grade());
-                        }
-
-                        if pb.grade() >= Grade::A {
-                            stats.decrement_grade(pb.grade());
-                        }
-                    }
-                } else if score.grade() >= Grade::A {
-                    stats.increment_grade(score.grade());
-                }
-
-                stats.rscore += additional_rscore as u64;
-
-                if score.pp > 0.0 && (recalculate(&state.db, &mut stats).await).is_err() {
-                    tracing::error!("recalculate failed for user: {}", user.name());
-                    return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
-                }
-
+                    "Your pp couldn't be calculated right now, it will show up shortly!",
+                )
+                .await?;
+            } else if ranked_best {
                 // casts as i32 so "let there be negative"
                 // TODO: is this really a good name
                 let pp_lost_gained = stats.pp as i32 - prev_stats.pp as i32;
@@ -559,38 +668,164 @@ pub async fn submit_score(
                     notify_message += &format!(" but lost {:.2}pp!", pp_lost_gained.abs());
                 }
 
-                {
-                    let r = state.redis.clone();
-                    tokio::spawn(async move {
-                        let _ = notify::notify(&r, user.id, &notify_message).await;
-                    });
-                }
+                notify::notify(&mut *tx, user.id, &notify_message).await?;
+            }
 
-                if let Ok(new_rank) = repository::stats::update_rank(
-                    &state.redis,
-                    &stats,
-                    &user.country,
-                    user.restricted(),
-                )
-                .await
-                {
-                    stats.rank = new_rank;
-                }
+            if !user.restricted() {
+                refresh_stats::refresh_stats(&mut *tx, user.id).await?;
             }
+
+            // we tell bancho that we have a new score
+            // for "recent score"
+            score::score_submitted(&mut *tx, score.id).await?;
+
+            anyhow::Ok(())
+        };
+
+        if let Err(e) = events.await {
+            tracing::error!(
+                "failed to write submission events for user {}: {e}",
+                user.name()
+            );
+            return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
         }
 
-        if (repository::stats::save(&state.db, &stats).await).is_err() {
-            tracing::error!("stats save failed for user: {}", user.name());
+        if let Err(e) = tx.commit().await {
+            tracing::error!("submission commit failed for user {}: {e}", user.name());
             return (StatusCode::INTERNAL_SERVER_ERROR, b"error: no").into_response();
         }
 
-        if !user.restricted() {
-            let r = state.redis.clone();
+        state.outbox.wake();
+
+        // the score is in, everything below only goes out after the commit.
+
+        let _ = state.metrics.incr("score.submitted", ["status:all"]);
+
+        if score.status == SubmissionStatus::Best.as_i32() {
+            let _ = state.metrics.incr("score.submitted", ["status:best"]);
+
+            invalidate_map(&state.redis, &score.map_md5).await;
+        }
+
+        if let Some(prev_holder) = first_place {
+            let webhook = first_place_webhook(
+                &user,
+                &score,
+                &beatmap,
+                &state.config.webhook.score,
+                prev_holder,
+            );
+
             tokio::spawn(async move {
-                let _ = refresh_stats::refresh_stats(&r, user.id).await;
+                let _ = webhook.post().await;
             });
         }
 
+        if score.passed && submission.replay_file.len() >= MIN_REPLAY_SIZE {
+            if !submission.lazer_data.is_empty()
+                && submission.refx()
+                && let Err(e) = state
+                    .storage
+                    .save_lazer_replay(score.id, &submission.lazer_data)
+                    .await
+            {
+                tracing::warn!("failed to save lazer payload for score {}: {e}", score.id);
+            }
+
+            if state
+                .storage
+                .save_replay(score.id, &submission.replay_file)
+                .await
+                .is_ok()
+            {
+                let state = state.clone();
+                let user = user.clone();
+                let score = score.clone();
+                let beatmap = beatmap.clone();
+
+                tokio::spawn(async move {
+                    match analyse_score(
+                        &state.db,
+                        &state.storage,
+                        &state.config.omajinai,
+                        &score,
+                        &beatmap,
+                    )
+                    .await
+                    {
+                        Ok(analysis) if analysis.flagged => {
+                            let _ = state.metrics.incr("score.review_flagged", ["status:ok"]);
+
+                            let _ = review_webhook(
+                                &state.config.webhook.debug,
+                                &user,
+                                &score,
+                                &beatmap,
+                                &analysis,
+                            )
+                            .post()
+                            .await;
+                        },
+                        Ok(_) => {},
+                        Err(e) => {
+                            tracing::warn!("replay analysis failed for score {}: {e}", score.id);
+                        },
+                    }
+                });
+            }
+
+            if let Some(fingerprint) = fingerprint {
+                let state = state.clone();
+                let user = user.clone();
+                let score = score.clone();
+                let beatmap = beatmap.clone();
+
+                tokio::spawn(async move {
+                    if let Err(e) = repository::fingerprint::insert(
+                        &state.db,
+                        score.id,
+                        &score.map_md5,
+                        score.userid,
+                        &fingerprint.to_bytes(),
+                    )
+                    .await
+                    {
+                        tracing::warn!(
+                            "failed to store the fingerprint of score {}: {e}",
+                            score.id
+                        );
+                    }
+
+                    if let Some(original_id) = duplicate_of {
+                        let _ = Webhook::new(&state.config.webhook.debug)
+                            .content(format!(
+                                "[{}] {} submitted a stolen replay on {} (score {} matches score {})",
+                                score.mode().as_str(),
+                                user.name(),
+                                beatmap.full_name(),
+                                score.id,
+                                original_id,
+                            ))
+                            .post()
+                            .await;
+                    }
+                });
+            }
+        }
+
+        if ranked_best
+            && !pp_pending
+            && let Ok(new_rank) = repository::stats::update_rank(
+                &state.redis,
+                &stats,
+                &user.country,
+                user.restricted(),
+            )
+            .await
+        {
+            stats.rank = new_rank;
+        }
+
         if !user.restricted() {
             let d = state.db.clone();
             let mut b = beatmap.clone();
@@ -599,15 +834,6 @@ pub async fn submit_score(
             });
         }
 
-        // we tell bancho that we have a new score
-        // for "recent score"
-        {
-            let r = state.redis.clone();
-            tokio::spawn(async move {
-                let _ = score::score_submitted(&r, score.id).await;
-            });
-        }
-
         let done = now.elapsed();
 
         let _ = state.metrics.timing(
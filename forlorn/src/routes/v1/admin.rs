@@ -0,0 +1,193 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde_json::{Value, json};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    constants::OutboxStatus,
+    dto::v1::{
+        admin::{GetBeatmapCache, GetOutboxEvents, InvalidateBeatmapCache, ReplayOutboxEvents},
+        tournament::CreateTournament,
+    },
+    repository::{beatmap::BEATMAP_CACHE, beatmap_cache::Eviction},
+    state::AppState,
+    usecases::tournament,
+};
+
+const DEFAULT_OUTBOX_LIMIT: u32 = 50;
+const MAX_OUTBOX_LIMIT: u32 = 500;
+
+const DEFAULT_BEATMAP_CACHE_LIMIT: usize = 100;
+
+/// the admin api is disabled until `ADMIN_KEY` is set.
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, Json<Value>)> {
+    if state.config.admin_key.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "reason": "Not found." })),
+        ));
+    }
+
+    let key = headers
+        .get("x-admin-key")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+
+    // constant time, so the key can't be worked out a byte at a time
+    if !bool::from(key.as_bytes().ct_eq(state.config.admin_key.as_bytes())) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "reason": "Invalid admin key." })),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_outbox_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<GetOutboxEvents>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(response) = authorize(&state, &headers) {
+        return response;
+    }
+
+    let status = match query.status.as_deref() {
+        None => OutboxStatus::Failed,
+        Some(status) => match OutboxStatus::parse(status) {
+            Some(status) => status,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "reason": "Unknown status." })),
+                );
+            },
+        },
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_OUTBOX_LIMIT)
+        .min(MAX_OUTBOX_LIMIT);
+
+    match state.outbox.fetch_by_status(status, limit).await {
+        Ok(events) => (StatusCode::OK, Json(json!({ "events": events }))),
+        Err(e) => {
+            tracing::error!("failed to fetch outbox events: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch events." })),
+            )
+        },
+    }
+}
+
+pub async fn replay_outbox_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ReplayOutboxEvents>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(response) = authorize(&state, &headers) {
+        return response;
+    }
+
+    match state.outbox.requeue_failed(query.event_id).await {
+        Ok(requeued) => (StatusCode::OK, Json(json!({ "requeued": requeued }))),
+        Err(e) => {
+            tracing::error!("failed to requeue outbox events: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to requeue events." })),
+            )
+        },
+    }
+}
+
+pub async fn create_tournament(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(submission): Json<CreateTournament>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(response) = authorize(&state, &headers) {
+        return response;
+    }
+
+    if submission.ends_at <= submission.starts_at {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "reason": "The tournament has to end after it starts." })),
+        );
+    }
+
+    if submission.maps.is_empty() || submission.maps.iter().any(|md5| md5.len() != 32) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "reason": "Invalid map pool." })),
+        );
+    }
+
+    match tournament::create_tournament(&state.db, &submission).await {
+        Ok(tournament_id) => (StatusCode::OK, Json(json!({ "id": tournament_id }))),
+        Err(e) => {
+            tracing::error!("failed to create tournament {}: {e}", submission.name);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to create tournament." })),
+            )
+        },
+    }
+}
+
+pub async fn get_beatmap_cache(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<GetBeatmapCache>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(response) = authorize(&state, &headers) {
+        return response;
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_BEATMAP_CACHE_LIMIT);
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "stats": BEATMAP_CACHE.stats(),
+            "entries": BEATMAP_CACHE.entries(limit),
+        })),
+    )
+}
+
+pub async fn invalidate_beatmap_cache(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<InvalidateBeatmapCache>,
+) -> (StatusCode, Json<Value>) {
+    if let Err(response) = authorize(&state, &headers) {
+        return response;
+    }
+
+    let eviction = match (&query.map_md5, query.map_id, query.set_id) {
+        (Some(md5), _, _) => Eviction::Md5(md5.clone()),
+        (_, Some(id), _) => Eviction::Id(id),
+        (_, _, Some(set_id)) => Eviction::Set(set_id),
+        _ if query.all => Eviction::All,
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    json!({ "reason": "Give md5, id or set_id, or all=true to drop everything." }),
+                ),
+            );
+        },
+    };
+    let removed = BEATMAP_CACHE.invalidate(eviction);
+
+    tracing::info!("{removed} beatmaps dropped from the cache");
+
+    (StatusCode::OK, Json(json!({ "removed": removed })))
+}
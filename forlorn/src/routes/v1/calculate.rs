@@ -70,8 +70,8 @@ pub async fn get_calculate_map(
 
         for accuracy in COMMON_ACCURACY {
             let (pp, star, _) = calculate_performance(
-                &state.config.omajinai,
-                beatmap.id,
+                &state.performance,
+                &beatmap,
                 mode,
                 mods,
                 max_combo,
@@ -106,8 +106,8 @@ pub async fn get_calculate_map(
     }
 
     let (pp, stars, hypothetical_pp) = calculate_performance(
-        &state.config.omajinai,
-        beatmap.id,
+        &state.performance,
+        &beatmap,
         mode,
         mods,
         max_combo,
@@ -0,0 +1,103 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use serde_json::{Value, json};
+
+use crate::{
+    constants::{GameMode, Mods},
+    dto::v1::clan::{GetClanMapRanking, GetClanStats},
+    repository,
+    state::AppState,
+};
+
+const DEFAULT_RANKING_LIMIT: u32 = 50;
+const MAX_RANKING_LIMIT: u32 = 100;
+
+pub async fn get_map_ranking(
+    State(state): State<AppState>,
+    Query(query): Query<GetClanMapRanking>,
+) -> (StatusCode, Json<Value>) {
+    let order_by = match query.metric.as_deref() {
+        None | Some("pp") => "pp",
+        Some("score") => "score",
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "reason": "Unknown metric." })),
+            );
+        },
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RANKING_LIMIT)
+        .min(MAX_RANKING_LIMIT);
+
+    match repository::clan::fetch_map_ranking(
+        &state.db,
+        &query.map_md5,
+        query.mode,
+        order_by,
+        limit,
+    )
+    .await
+    {
+        Ok(clans) => (StatusCode::OK, Json(json!({ "clans": clans }))),
+        Err(e) => {
+            tracing::error!("failed to rank clans on {}: {e}", query.map_md5);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch clans." })),
+            )
+        },
+    }
+}
+
+pub async fn get_clan_stats(
+    State(state): State<AppState>,
+    Query(query): Query<GetClanStats>,
+) -> (StatusCode, Json<Value>) {
+    let clan = match repository::clan::fetch_by_id(&state.db, query.clan_id).await {
+        Ok(Some(clan)) => clan,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "reason": "Clan not found." })),
+            );
+        },
+        Err(e) => {
+            tracing::error!("failed to fetch clan {}: {e}", query.clan_id);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch clan." })),
+            );
+        },
+    };
+
+    let totals = match repository::clan::fetch_mode_totals(&state.db, clan.id).await {
+        Ok(totals) => totals,
+        Err(e) => {
+            tracing::error!("failed to fetch the stats of clan {}: {e}", clan.id);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch clan stats." })),
+            );
+        },
+    };
+
+    let modes: Vec<Value> = totals
+        .iter()
+        .map(|stats| {
+            let mode = GameMode::from_params(stats.mode as i32, Mods::NOMOD);
+
+            json!({ "name": mode.as_str(), "stats": stats })
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(json!({ "clan": clan, "modes": modes })),
+    )
+}
@@ -0,0 +1,91 @@
+use axum::{Json, extract::State, http::StatusCode};
+use serde_json::{Value, json};
+
+use crate::{
+    dto::v1::lazer::SubmitLazerScore,
+    repository,
+    state::AppState,
+    usecases::{
+        lazer::{LazerSubmissionError, submit_lazer_score},
+        password::verify_password,
+    },
+};
+
+pub async fn submit_score(
+    State(state): State<AppState>,
+    Json(submission): Json<SubmitLazerScore>,
+) -> (StatusCode, Json<Value>) {
+    let user = match repository::user::fetch_by_name(&state.db, &submission.username).await {
+        Ok(Some(user)) => user,
+        _ => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "reason": "Invalid credentials." })),
+            );
+        },
+    };
+
+    if !matches!(
+        verify_password(&submission.password_md5, &user.pw_bcrypt).await,
+        Ok(true)
+    ) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "reason": "Invalid credentials." })),
+        );
+    }
+
+    let beatmap =
+        match repository::beatmap::fetch_by_md5(&state.config, &state.db, &submission.beatmap_md5)
+            .await
+        {
+            Ok(Some(beatmap)) => beatmap,
+            _ => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(json!({ "reason": "Beatmap not found." })),
+                );
+            },
+        };
+
+    match submit_lazer_score(&state, &user, &beatmap, &submission).await {
+        Ok(score) => {
+            tracing::info!(
+                "[{}] {} submitted a lazer score on {} ({}pp)",
+                score.mode().as_str(),
+                user.name(),
+                beatmap.id,
+                score.pp,
+            );
+
+            (
+                StatusCode::OK,
+                Json(json!({
+                    "id": score.id,
+                    "status": score.status,
+                    "pp": score.pp,
+                    "acc": score.acc,
+                    "grade": score.grade,
+                })),
+            )
+        },
+        Err(LazerSubmissionError::Invalid(reason)) => {
+            (StatusCode::BAD_REQUEST, Json(json!({ "reason": reason })))
+        },
+        Err(LazerSubmissionError::Duplicate) => (
+            StatusCode::CONFLICT,
+            Json(json!({ "reason": "Score already submitted." })),
+        ),
+        Err(LazerSubmissionError::Busy) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "reason": "Score is already being submitted, try again." })),
+        ),
+        Err(LazerSubmissionError::Internal(e)) => {
+            tracing::error!("lazer submission failed for {}: {e}", user.name());
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to submit score." })),
+            )
+        },
+    }
+}
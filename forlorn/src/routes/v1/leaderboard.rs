@@ -0,0 +1,181 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use serde_json::{Value, json};
+
+use crate::{
+    constants::LeaderboardType,
+    dto::v1::leaderboard::GetLeaderboard,
+    models::LeaderboardScore,
+    repository::{self, leaderboard::LeaderboardFilter},
+    state::AppState,
+    usecases::leaderboard::{mods_filter, resolve_tournament},
+};
+
+const MAX_LEADERBOARD_LIMIT: u32 = 100;
+
+fn score_json(score: &LeaderboardScore, rank: u32, is_refx: bool) -> Value {
+    let clock_rate = score.clock_rate();
+
+    let mut entry = json!({
+        "rank": rank,
+        "id": score.id,
+        "user": { "id": score.userid, "name": score.name },
+        "clan_tag": score.clan_tag,
+        "pp": score.pp,
+        "score": score.actual_score,
+        "acc": score.acc,
+        "max_combo": score.max_combo,
+        "mods": score.mods,
+        "mods_str": score.mods().as_str((clock_rate > 0.0).then_some(clock_rate)),
+        "grade": score.grade,
+        "play_time": score.play_time,
+    });
+
+    if is_refx {
+        entry["cheat_values"] = json!({
+            "aim_assist_type": score.aim_assist_type,
+            "maple_values": score.maple_values.as_ref().map(|m| &m.0),
+            "aim_correction_value": score.aim_correction_value,
+            "ar_changer_value": score.ar_changer_value,
+            "uses_ar_changer": score.uses_ar_changer,
+            "uses_cs_changer": score.uses_cs_changer,
+            "uses_timewarp": score.uses_timewarp,
+            "timewarp_value": score.timewarp_value,
+            "uses_hd_remover": score.uses_hd_remover,
+            "clock_rate": score.clock_rate,
+        });
+    }
+
+    entry
+}
+
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<GetLeaderboard>,
+) -> (StatusCode, Json<Value>) {
+    let scoring_metric = match query.metric.as_deref() {
+        None | Some("pp") => "pp",
+        Some("score") => "score",
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "reason": "Unknown metric." })),
+            );
+        },
+    };
+
+    let user = match query.user_id {
+        Some(user_id) => match repository::user::fetch_by_id(&state.db, &user_id).await {
+            Ok(Some(user)) if !user.restricted() => Some(user),
+            Ok(_) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(json!({ "reason": "User not found." })),
+                );
+            },
+            Err(e) => {
+                tracing::error!("failed to fetch user {user_id}: {e}");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "reason": "Failed to fetch user." })),
+                );
+            },
+        },
+        None => None,
+    };
+
+    let leaderboard_type = LeaderboardType::from_i32(query.leaderboard_type);
+
+    let country = query
+        .country
+        .as_deref()
+        .or(user.as_ref().map(|u| u.country.as_str()))
+        .map(str::to_lowercase);
+
+    let missing = match leaderboard_type {
+        LeaderboardType::Friends | LeaderboardType::Clan => user.is_none(),
+        LeaderboardType::Country => country.is_none(),
+        _ => false,
+    };
+
+    if missing {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "reason": "Missing user or country." })),
+        );
+    }
+
+    let friend_ids = match (&user, leaderboard_type) {
+        (Some(user), LeaderboardType::Friends) => {
+            let mut friends = repository::user::fetch_friend_ids(&state.db, user.id)
+                .await
+                .unwrap_or_default();
+
+            friends.push(user.id);
+            friends
+        },
+        _ => Vec::new(),
+    };
+
+    let (leaderboard_type, tournament) =
+        resolve_tournament(&state.db, leaderboard_type, &query.map_md5).await;
+
+    let (mods, mods_mask) = mods_filter(&state.config, query.mods);
+
+    let filter = LeaderboardFilter {
+        map_md5: &query.map_md5,
+        mode: query.mode,
+        user_id: user.as_ref().map_or(0, |u| u.id),
+        leaderboard_type,
+        mods,
+        mods_mask,
+        country: country.as_deref().unwrap_or_default(),
+        friend_ids: &friend_ids,
+        clan_id: user.as_ref().map_or(0, |u| u.clan_id),
+        scoring_metric,
+        lazer: query.lazer && tournament.is_none(),
+        tournament: tournament.as_ref(),
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(state.config.leaderboard_size)
+        .min(MAX_LEADERBOARD_LIMIT);
+
+    match repository::leaderboard::fetch_leaderboard_scores(
+        &state.db,
+        &filter,
+        query.refx,
+        limit,
+        query.offset,
+    )
+    .await
+    {
+        Ok(scores) => {
+            let scores: Vec<Value> = scores
+                .iter()
+                .enumerate()
+                .map(|(idx, score)| score_json(score, query.offset + idx as u32 + 1, query.refx))
+                .collect();
+
+            (
+                StatusCode::OK,
+                Json(json!({
+                    "scores": scores,
+                    "limit": limit,
+                    "offset": query.offset,
+                })),
+            )
+        },
+        Err(e) => {
+            tracing::error!("failed to fetch the leaderboard of {}: {e}", query.map_md5);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch leaderboard." })),
+            )
+        },
+    }
+}
@@ -1,9 +1,17 @@
+pub mod admin;
 pub mod calculate;
+pub mod clan;
 pub mod client;
 pub mod health;
+pub mod lazer;
+pub mod leaderboard;
 pub mod replay;
+pub mod tournament;
 
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{get, post},
+};
 
 use crate::state::AppState;
 
@@ -13,4 +21,20 @@ pub fn create_routes() -> Router<AppState> {
         .route("/calculate", get(calculate::get_calculate_map))
         .route("/latest_refx_client_hash", get(client::get_client))
         .route("/get_replay", get(replay::get_replay))
+        .route("/get_lazer_replay", get(replay::get_lazer_replay))
+        .route("/replays/most_watched", get(replay::get_most_watched))
+        .route("/leaderboard", get(leaderboard::get_leaderboard))
+        .route("/lazer/submit", post(lazer::submit_score))
+        .route("/clans/map_ranking", get(clan::get_map_ranking))
+        .route("/clans/stats", get(clan::get_clan_stats))
+        .route("/tournaments/standings", get(tournament::get_standings))
+        // admin
+        .route("/admin/outbox", get(admin::get_outbox_events))
+        .route("/admin/outbox/replay", post(admin::replay_outbox_events))
+        .route("/admin/tournaments", post(admin::create_tournament))
+        .route("/admin/beatmap_cache", get(admin::get_beatmap_cache))
+        .route(
+            "/admin/beatmap_cache/invalidate",
+            post(admin::invalidate_beatmap_cache),
+        )
 }
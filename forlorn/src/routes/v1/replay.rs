@@ -1,29 +1,135 @@
 use axum::{
+    Json,
     body::Body,
     extract::{Query, State},
     http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
+use serde_json::{Value, json};
 
-use crate::{dto::v1::replay::GetReplay, state::AppState};
+use crate::{
+    dto::v1::replay::{GetMostWatched, GetReplay},
+    models::{Score, User},
+    repository,
+    state::AppState,
+    usecases::{
+        password::verify_password,
+        replay::{build_osr, fetch_viewable_replay},
+    },
+};
+
+const DEFAULT_MOST_WATCHED_LIMIT: u32 = 50;
+const MAX_MOST_WATCHED_LIMIT: u32 = 100;
+
+/// the score & player behind the replay, `Err` is always a plain 404.
+async fn authorize(state: &AppState, replay: &GetReplay) -> Result<(Score, User), Response> {
+    let viewer = match (&replay.username, &replay.password_md5) {
+        (Some(username), Some(password_md5)) => {
+            let user = match repository::user::fetch_by_name(&state.db, username).await {
+                Ok(Some(user)) => user,
+                _ => return Err(StatusCode::NOT_FOUND.into_response()),
+            };
+
+            match verify_password(password_md5, &user.pw_bcrypt).await {
+                Ok(true) => Some(user),
+                _ => return Err(StatusCode::NOT_FOUND.into_response()),
+            }
+        },
+        _ => None,
+    };
+
+    match fetch_viewable_replay(&state.db, viewer.as_ref(), replay.score_id).await {
+        Ok(Some(viewable)) => Ok(viewable),
+        _ => Err(StatusCode::NOT_FOUND.into_response()),
+    }
+}
 
 pub async fn get_replay(
     State(state): State<AppState>,
     Query(replay): Query<GetReplay>,
 ) -> impl IntoResponse {
-    let replay_data = match state.storage.load_replay(replay.score_id).await {
+    let (score, user) = match authorize(&state, &replay).await {
+        Ok(viewable) => viewable,
+        Err(response) => return response,
+    };
+
+    let Ok(Some(beatmap)) =
+        repository::beatmap::fetch_by_md5(&state.config, &state.db, &score.map_md5).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let frames = match state.storage.load_replay(replay.score_id).await {
         Ok(data) => data,
         Err(_) => return StatusCode::NOT_FOUND.into_response(),
     };
 
+    let osr = build_osr(&score, &user, &beatmap, &frames);
+
+    // the client doesn't care about the name, but people downloading it do.
+    let filename = format!(
+        "{} - {} ({})",
+        user.name,
+        beatmap.full_name(),
+        score.play_time.format("%Y-%m-%d")
+    )
+    .replace(['"', '/', '\\'], "_");
+
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/octet-stream")
         .header(
             header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}.osr\"", replay.score_id),
+            format!("attachment; filename=\"{filename}.osr\""),
         )
-        .body(Body::from(replay_data))
+        .body(Body::from(osr))
         .unwrap()
         .into_response()
 }
+
+/// the extra data the refx client sends with a replay, if there's any.
+pub async fn get_lazer_replay(
+    State(state): State<AppState>,
+    Query(replay): Query<GetReplay>,
+) -> impl IntoResponse {
+    if let Err(response) = authorize(&state, &replay).await {
+        return response;
+    }
+
+    let payload = match state.storage.load_lazer_replay(replay.score_id).await {
+        Ok(data) => data,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.osr-lazer\"", replay.score_id),
+        )
+        .body(Body::from(payload))
+        .unwrap()
+        .into_response()
+}
+
+pub async fn get_most_watched(
+    State(state): State<AppState>,
+    Query(query): Query<GetMostWatched>,
+) -> (StatusCode, Json<Value>) {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_MOST_WATCHED_LIMIT)
+        .min(MAX_MOST_WATCHED_LIMIT);
+
+    match repository::replay_view::fetch_most_watched(&state.db, query.mode, limit).await {
+        Ok(replays) => (StatusCode::OK, Json(json!({ "replays": replays }))),
+        Err(e) => {
+            tracing::error!("failed to fetch the most watched replays: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch replays." })),
+            )
+        },
+    }
+}
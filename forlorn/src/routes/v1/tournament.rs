@@ -0,0 +1,82 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use serde_json::{Value, json};
+
+use crate::{dto::v1::tournament::GetStandings, repository, state::AppState};
+
+const DEFAULT_STANDINGS_LIMIT: u32 = 50;
+const MAX_STANDINGS_LIMIT: u32 = 500;
+
+pub async fn get_standings(
+    State(state): State<AppState>,
+    Query(query): Query<GetStandings>,
+) -> (StatusCode, Json<Value>) {
+    let order_by = match query.metric.as_deref() {
+        None | Some("pp") => "pp",
+        Some("score") => "score",
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "reason": "Unknown metric." })),
+            );
+        },
+    };
+
+    let tournament = match repository::tournament::fetch_by_id(&state.db, query.tournament_id).await
+    {
+        Ok(Some(tournament)) => tournament,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "reason": "Tournament not found." })),
+            );
+        },
+        Err(e) => {
+            tracing::error!("failed to fetch tournament {}: {e}", query.tournament_id);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch tournament." })),
+            );
+        },
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_STANDINGS_LIMIT)
+        .min(MAX_STANDINGS_LIMIT);
+
+    let standings = repository::tournament::fetch_standings(
+        &state.db,
+        &tournament,
+        query.mode,
+        order_by,
+        limit,
+    )
+    .await;
+
+    let maps = repository::tournament::fetch_map_md5s(&state.db, tournament.id).await;
+
+    match (standings, maps) {
+        (Ok(standings), Ok(maps)) => (
+            StatusCode::OK,
+            Json(json!({
+                "tournament": tournament,
+                "maps": maps,
+                "standings": standings,
+            })),
+        ),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!(
+                "failed to fetch the standings of tournament {}: {e}",
+                tournament.id
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "reason": "Failed to fetch standings." })),
+            )
+        },
+    }
+}
@@ -1,6 +1,5 @@
 use std::sync::Arc;
 
-use dashmap::DashSet;
 use dogstatsd::Client as DatadogClient;
 use rslock::LockManager;
 use storage::Storage;
@@ -9,6 +8,8 @@ use crate::{
     config::Config,
     infrastructure::{
         database::DbPoolManager,
+        outbox::Outbox,
+        performance::Performance,
         redis::{RedisConnectionManager, RedisPubsubManager},
     },
 };
@@ -20,32 +21,34 @@ pub struct AppState {
     pub db: DbPoolManager,
     pub redis: RedisConnectionManager,
     pub subscriber: RedisPubsubManager,
+    pub outbox: Outbox,
+    pub performance: Performance,
     pub score_locks: LockManager,
     pub metrics: Arc<DatadogClient>,
-    pub unsubmitted_maps: Arc<DashSet<String>>,
-    pub needs_update_maps: Arc<DashSet<String>>,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Arc<Config>,
         storage: Storage,
         db: DbPoolManager,
         redis: RedisConnectionManager,
         subscriber: RedisPubsubManager,
+        outbox: Outbox,
         score_locks: LockManager,
         metrics: Arc<DatadogClient>,
     ) -> Self {
         Self {
+            performance: Performance::from_config(&config),
             config,
             storage,
             db,
             redis,
             subscriber,
+            outbox,
             score_locks,
             metrics,
-            unsubmitted_maps: Arc::new(DashSet::new()),
-            needs_update_maps: Arc::new(DashSet::new()),
         }
     }
 }
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use storage::Storage;
+use webhook::Webhook;
+
+use crate::{
+    config::OmajinaiConfig,
+    constants::Mods,
+    infrastructure::database::DbPoolManager,
+    models::{Beatmap, Score, ScoreAnalysis, User},
+    osu_file::OsuFile,
+    replay::{Replay, analysis::analyse},
+    repository,
+    usecases::{beatmap::fetch_osu_file, replay::reported_clock_rate},
+};
+
+/// runs the statistical checks on a stored replay and saves the results.
+///
+/// flagged scores are only meant for a human to look at.
+pub async fn analyse_score(
+    db: &DbPoolManager,
+    storage: &Storage,
+    config: &OmajinaiConfig,
+    score: &Score,
+    beatmap: &Beatmap,
+) -> Result<ScoreAnalysis> {
+    let data = storage.load_replay(score.id).await?;
+    let replay = Replay::from_frames(&data).or_else(|_| Replay::from_osr(&data))?;
+
+    // ur is optional, so don't fail the whole thing if the map is missing
+    let hit_objects = match fetch_osu_file(config, beatmap).await {
+        Ok(osu) => hit_object_times(&osu),
+        Err(_) => Vec::new(),
+    };
+
+    let stats = analyse(
+        &replay,
+        score.mode(),
+        &hit_objects,
+        hit_window(score, beatmap),
+        reported_clock_rate(score),
+    );
+
+    let analysis = ScoreAnalysis::from_statistics(score, stats);
+    repository::analysis::save(db, &analysis).await?;
+
+    Ok(analysis)
+}
+
+pub fn review_webhook(
+    url: &str,
+    user: &User,
+    score: &Score,
+    beatmap: &Beatmap,
+    analysis: &ScoreAnalysis,
+) -> Webhook {
+    Webhook::new(url).content(format!(
+        "[{}] {} needs review on {} (score {}) [{}]",
+        score.mode().as_str(),
+        user.name(),
+        beatmap.full_name(),
+        score.id,
+        analysis.flag_reasons.as_deref().unwrap_or_default(),
+    ))
+}
+
+/// the widest window (ms) that still counts as a hit.
+fn hit_window(score: &Score, beatmap: &Beatmap) -> f32 {
+    let mods = score.mods();
+
+    let od = if mods.contains(Mods::HARDROCK) {
+        (beatmap.od * 1.4).min(10.0)
+    } else if mods.contains(Mods::EASY) {
+        beatmap.od / 2.0
+    } else {
+        beatmap.od
+    };
+
+    match score.mode().as_vanilla() {
+        1 => 120.0 - 8.0 * od,
+        _ => 200.0 - 10.0 * od,
+    }
+}
+
+/// start times of every circle and slider,
+/// spinners doesn't have a hit error.
+fn hit_object_times(osu: &OsuFile) -> Vec<i32> {
+    osu.hit_objects
+        .iter()
+        .filter(|object| object.is_circle() || object.is_slider())
+        .map(|object| object.time as i32)
+        .collect()
+}
@@ -1,18 +1,121 @@
-use std::sync::LazyLock;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
+use dogstatsd::Client as DatadogClient;
+use tokio::sync::mpsc::UnboundedReceiver;
 
-use crate::{config::OmajinaiConfig, infrastructure::database::DbPoolManager, models::Beatmap};
+use crate::{
+    config::OmajinaiConfig,
+    infrastructure::{
+        database::DbPoolManager,
+        omajinai::beatmap::{api_ensure_osu_file, load_osu_file},
+        redis::{RedisConnectionManager, publish::evict_map::evict_map},
+    },
+    models::Beatmap,
+    osu_file::OsuFile,
+    repository::{
+        self,
+        beatmap::{BEATMAP_CACHE, PRIVATE_INITIAL_SET_ID},
+        beatmap_cache::Eviction,
+    },
+};
 
-static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+const CACHE_METRICS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// the beatmap cache only keeps counters, this ships what changed since last time.
+pub async fn report_cache_metrics(metrics: Arc<DatadogClient>) {
+    let mut last = BEATMAP_CACHE.stats();
+
+    loop {
+        tokio::time::sleep(CACHE_METRICS_INTERVAL).await;
+
+        let stats = BEATMAP_CACHE.stats();
+
+        let _ = metrics.count(
+            "beatmap_cache.lookup",
+            (stats.hits - last.hits) as i64,
+            ["status:hit"],
+        );
+        let _ = metrics.count(
+            "beatmap_cache.lookup",
+            (stats.misses - last.misses) as i64,
+            ["status:miss"],
+        );
+        let _ = metrics.count(
+            "beatmap_cache.evicted",
+            (stats.evictions - last.evictions) as i64,
+            ["status:ok"],
+        );
+        let _ = metrics.gauge("beatmap_cache.size", stats.size.to_string(), ["status:ok"]);
+
+        last = stats;
+    }
+}
+
+/// tells the other replicas about every map this one dropped on purpose.
+pub async fn broadcast_evictions(
+    redis: RedisConnectionManager,
+    mut evictions: UnboundedReceiver<Eviction>,
+) {
+    while let Some(eviction) = evictions.recv().await {
+        if let Err(e) = evict_map(&redis, &eviction).await {
+            tracing::warn!("failed to broadcast beatmap eviction {eviction}: {e}");
+        }
+    }
+}
 
 pub async fn ensure_osu_file(config: &OmajinaiConfig, beatmap: &Beatmap) -> Result<bool> {
-    let url = format!(
-        "{}/v1/ensure-osu/{}?md5={}",
-        config.beatmap_service_url, beatmap.id, beatmap.md5
-    );
-    let resp = CLIENT.get(&url).send().await?;
-    Ok(resp.status().is_success())
+    api_ensure_osu_file(config, beatmap).await
+}
+
+pub async fn fetch_osu_file(config: &OmajinaiConfig, beatmap: &Beatmap) -> Result<OsuFile> {
+    let data = load_osu_file(config, beatmap).await?;
+
+    OsuFile::parse(&String::from_utf8_lossy(&data))
+}
+
+/// private maps never went through the osu! api, so anything we know
+/// about them has to come from the .osu file itself.
+///
+/// returns whether anything was filled in.
+pub async fn fill_private_map(
+    config: &OmajinaiConfig,
+    db: &DbPoolManager,
+    beatmap: &mut Beatmap,
+) -> Result<bool> {
+    if beatmap.set_id < PRIVATE_INITIAL_SET_ID
+        || (beatmap.max_combo > 0 && beatmap.total_length > 0)
+    {
+        return Ok(false);
+    }
+
+    let osu = fetch_osu_file(config, beatmap).await?;
+
+    if !osu.metadata.artist.is_empty() {
+        beatmap.artist = osu.metadata.artist.clone();
+    }
+    if !osu.metadata.title.is_empty() {
+        beatmap.title = osu.metadata.title.clone();
+    }
+    if !osu.metadata.version.is_empty() {
+        beatmap.version = osu.metadata.version.clone();
+    }
+    if !osu.metadata.creator.is_empty() {
+        beatmap.creator = osu.metadata.creator.clone();
+    }
+
+    beatmap.mode = osu.general.mode as i8;
+    beatmap.total_length = osu.total_length();
+    beatmap.max_combo = osu.max_combo() as i32;
+    beatmap.bpm = osu.bpm();
+    beatmap.cs = osu.difficulty.cs;
+    beatmap.ar = osu.difficulty.ar;
+    beatmap.od = osu.difficulty.od;
+    beatmap.hp = osu.difficulty.hp;
+
+    repository::beatmap::update_metadata(db, beatmap).await?;
+
+    Ok(true)
 }
 
 pub async fn increment_playcount(
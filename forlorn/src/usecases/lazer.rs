@@ -0,0 +1,354 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{Result, anyhow, bail};
+use md5::{Digest, Md5};
+
+use crate::{
+    constants::{GameMode, Grade, LAZER_ONLINE_CHECKSUM, Mods, SubmissionStatus},
+    dto::v1::lazer::{LazerMod, SubmitLazerScore},
+    infrastructure::redis::publish::{notify, refresh_stats},
+    models::{Beatmap, Score, User},
+    repository,
+    state::AppState,
+    usecases::{
+        beatmap::fetch_osu_file,
+        leaderboard::invalidate_map,
+        score::{
+            calculate_accuracy, calculate_grade, calculate_score_performance, calculate_status,
+            calculate_xp, update_any_preexisting_personal_best,
+        },
+        stats::apply_submission,
+        validation::{handle_violations, validate_hit_counts},
+    },
+};
+
+/// what went wrong with a lazer submission, so the route can tell
+/// the client apart from us.
+pub enum LazerSubmissionError {
+    Invalid(String),
+    Duplicate,
+    Busy,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for LazerSubmissionError {
+    fn from(e: anyhow::Error) -> Self {
+        LazerSubmissionError::Internal(e)
+    }
+}
+
+/// lazer acronyms that have a stable equivalent.
+/// classic doesn't change anything on our side so it's just dropped.
+fn mod_from_acronym(acronym: &str) -> Option<Mods> {
+    let mods = match acronym {
+        "NF" => Mods::NOFAIL,
+        "EZ" => Mods::EASY,
+        "TD" => Mods::TOUCHSCREEN,
+        "HD" => Mods::HIDDEN,
+        "HR" => Mods::HARDROCK,
+        "SD" => Mods::SUDDENDEATH,
+        "DT" => Mods::DOUBLETIME,
+        "RX" => Mods::RELAX,
+        "HT" => Mods::HALFTIME,
+        "NC" => Mods::NIGHTCORE | Mods::DOUBLETIME,
+        "FL" => Mods::FLASHLIGHT,
+        "AT" => Mods::AUTOPLAY,
+        "SO" => Mods::SPUNOUT,
+        "AP" => Mods::AUTOPILOT,
+        "PF" => Mods::PERFECT | Mods::SUDDENDEATH,
+        "1K" => Mods::KEY1,
+        "2K" => Mods::KEY2,
+        "3K" => Mods::KEY3,
+        "4K" => Mods::KEY4,
+        "5K" => Mods::KEY5,
+        "6K" => Mods::KEY6,
+        "7K" => Mods::KEY7,
+        "8K" => Mods::KEY8,
+        "9K" => Mods::KEY9,
+        "FI" => Mods::FADEIN,
+        "RD" => Mods::RANDOM,
+        "CN" => Mods::CINEMA,
+        "TP" => Mods::TARGET,
+        "DS" => Mods::KEYCOOP,
+        "MR" => Mods::MIRROR,
+        "CL" => Mods::NOMOD,
+        _ => return None,
+    };
+
+    Some(mods)
+}
+
+/// stable mods & clock rate (-1.0 when untouched) of the lazer mods.
+pub fn convert_mods(lazer_mods: &[LazerMod]) -> Result<(Mods, f64)> {
+    let mut mods = Mods::NOMOD;
+    let mut clock_rate = -1.0;
+
+    for lazer_mod in lazer_mods {
+        let Some(flag) = mod_from_acronym(&lazer_mod.acronym) else {
+            bail!("unsupported mod {}", lazer_mod.acronym);
+        };
+
+        mods |= flag;
+
+        if flag.intersects(Mods::DOUBLETIME | Mods::HALFTIME) {
+            let default_rate = if flag.contains(Mods::HALFTIME) { 0.75 } else { 1.5 };
+
+            clock_rate = lazer_mod
+                .settings
+                .get("speed_change")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(default_rate);
+        }
+    }
+
+    Ok((mods, clock_rate))
+}
+
+/// maps the lazer statistics onto the stable judgements of the mode.
+fn apply_statistics(score: &mut Score, statistics: &HashMap<String, i32>) {
+    let stat = |name: &str| statistics.get(name).copied().unwrap_or(0).max(0);
+
+    match score.mode().as_vanilla() {
+        1 => {
+            score.n300 = stat("great");
+            score.n100 = stat("ok");
+            score.nmiss = stat("miss");
+        },
+        2 => {
+            // fruits, drops & droplets
+            score.n300 = stat("great");
+            score.n100 = stat("large_tick_hit");
+            score.n50 = stat("small_tick_hit");
+            score.nkatu = stat("small_tick_miss");
+            score.nmiss = stat("miss") + stat("large_tick_miss");
+        },
+        3 => {
+            score.ngeki = stat("perfect");
+            score.n300 = stat("great");
+            score.nkatu = stat("good");
+            score.n100 = stat("ok");
+            score.n50 = stat("meh");
+            score.nmiss = stat("miss");
+        },
+        _ => {
+            score.n300 = stat("great");
+            score.n100 = stat("ok");
+            score.n50 = stat("meh");
+            score.nmiss = stat("miss");
+        },
+    }
+}
+
+/// dedupe key of a lazer score, they don't come with an online checksum.
+pub fn lazer_checksum(user_id: i32, submission: &SubmitLazerScore) -> String {
+    let data = format!(
+        "{}:{}:{}:{}:{}",
+        user_id,
+        submission.beatmap_md5,
+        submission.ruleset_id,
+        submission.total_score,
+        submission.ended_at.timestamp_millis(),
+    );
+
+    format!("{:x}", Md5::digest(data.as_bytes()))
+}
+
+pub fn score_from_lazer(
+    submission: &SubmitLazerScore,
+    beatmap: &Beatmap,
+    user_id: i32,
+) -> Result<Score> {
+    if !(0..=3).contains(&submission.ruleset_id) {
+        bail!("unknown ruleset {}", submission.ruleset_id);
+    }
+
+    let (mods, clock_rate) = convert_mods(&submission.mods)?;
+
+    let mut score = Score {
+        id: 0,
+        map_md5: beatmap.md5.clone(),
+        score: submission.classic_total_score.clamp(0, i32::MAX as i64) as i32,
+        xp: 0.0,
+        pp: 0.0,
+        acc: 0.0,
+        max_combo: submission.max_combo.max(0),
+        mods: mods.bits(),
+        n300: 0,
+        n100: 0,
+        n50: 0,
+        nmiss: 0,
+        ngeki: 0,
+        nkatu: 0,
+        grade: String::new(),
+        status: 0,
+        mode: GameMode::from_params(submission.ruleset_id, mods).as_i32(),
+        play_time: submission.ended_at,
+        time_elapsed: 0,
+        client_flags: 0,
+        userid: user_id,
+        perfect: false,
+        online_checksum: LAZER_ONLINE_CHECKSUM.into(),
+
+        aim_correction_value: 0,
+        ar_changer_value: -1.0,
+        uses_aim_correction: false,
+        uses_ar_changer: false,
+        uses_cs_changer: false,
+        uses_timewarp: false,
+        timewarp_value: -1.0,
+        uses_hd_remover: false,
+        aim_assist_type: 0,
+        clock_rate,
+        maple_values: None,
+        pinned: false,
+
+        rank: 0,
+        hypothetical_pp: 0.0,
+        stars: 0.0,
+        passed: submission.passed,
+        quit: false,
+    };
+
+    apply_statistics(&mut score, &submission.statistics);
+
+    score.acc = calculate_accuracy(&score);
+    score.perfect = score.nmiss == 0 && score.max_combo >= beatmap.max_combo;
+    score.grade = if score.passed { calculate_grade(&score) } else { Grade::F }
+        .as_str()
+        .into();
+
+    Ok(score)
+}
+
+/// runs a lazer score through the same status, pp & stats path as
+/// a stable one. lazer scores don't have replays, so no analysis.
+pub async fn submit_lazer_score(
+    state: &AppState,
+    user: &User,
+    beatmap: &Beatmap,
+    submission: &SubmitLazerScore,
+) -> Result<Score, LazerSubmissionError> {
+    let mut score = score_from_lazer(submission, beatmap, user.id)
+        .map_err(|e| LazerSubmissionError::Invalid(e.to_string()))?;
+
+    if score.mods().conflict() {
+        return Err(LazerSubmissionError::Invalid(
+            "Illegal mod combination.".into(),
+        ));
+    }
+
+    let checksum = lazer_checksum(user.id, submission);
+
+    let Ok(_submission_lock_) = state
+        .score_locks
+        .lock(
+            format!("refx:score_submission:{checksum}").as_bytes(),
+            Duration::from_secs(15),
+        )
+        .await
+    else {
+        return Err(LazerSubmissionError::Busy);
+    };
+
+    if repository::lazer_score::exists(&state.db, &checksum).await? {
+        let _ = state.metrics.incr("score.duplicate", ["status:ok"]);
+        return Err(LazerSubmissionError::Duplicate);
+    }
+
+    // the statistics come straight from the client, so they get the same limits as a stable score.
+    // only past the dedupe, so a retried post isn't flagged twice.
+    if score.passed {
+        let osu = fetch_osu_file(&state.config.omajinai, beatmap).await.ok();
+        let violations = validate_hit_counts(&score, beatmap, osu.as_ref());
+
+        if handle_violations(state, user, &score, beatmap, &violations).await {
+            return Err(LazerSubmissionError::Invalid(
+                "Score failed validation.".into(),
+            ));
+        }
+    }
+
+    let pp_pending = match calculate_score_performance(&state.performance, &score, beatmap).await {
+        Ok(result) => {
+            (score.pp, score.stars, score.hypothetical_pp) = result;
+            false
+        },
+        Err(e) => {
+            let _ = state.metrics.incr("score.pp_pending", ["status:ok"]);
+            tracing::warn!(
+                "pp calculation failed for {} on {}, deferring it: {e}",
+                user.name(),
+                beatmap.id,
+            );
+
+            score.passed
+        },
+    };
+
+    let mut tx = state.db.begin().await.map_err(anyhow::Error::from)?;
+    let mut prev_best = None;
+
+    if score.passed {
+        prev_best = calculate_status(&state.db, &mut score).await?;
+
+        if let Some(prev_best) = &prev_best {
+            repository::score::update_status(&mut tx, prev_best.id, prev_best.status).await?;
+        }
+    } else {
+        score.status = SubmissionStatus::Failed.as_i32();
+    }
+
+    score.xp = calculate_xp(&score, beatmap);
+
+    if score.status() == SubmissionStatus::Best {
+        update_any_preexisting_personal_best(&mut tx, &score).await?;
+    }
+
+    score.id = repository::score::insert(&mut tx, &score, beatmap).await?;
+    repository::lazer_score::insert(&mut tx, score.id, &checksum, submission).await?;
+
+    if pp_pending {
+        repository::pp_queue::insert(&mut tx, score.id).await?;
+    }
+
+    let mut stats = repository::stats::fetch_for_update(&mut tx, &state.redis, user.id, score.mode)
+        .await?
+        .ok_or_else(|| anyhow!("stats not found for user {}", user.id))?;
+
+    let ranked_best =
+        apply_submission(&mut tx, &mut stats, &score, beatmap, prev_best.as_ref()).await?;
+
+    tx.commit().await.map_err(anyhow::Error::from)?;
+
+    let _ = state.metrics.incr("score.submitted", ["status:lazer"]);
+
+    // lazer boards are built from every passed lazer score, not just bests
+    if score.passed {
+        invalidate_map(&state.redis, &score.map_md5).await;
+    }
+
+    if ranked_best && !pp_pending {
+        let _ =
+            repository::stats::update_rank(&state.redis, &stats, &user.country, user.restricted())
+                .await;
+    }
+
+    let outbox = state.outbox.clone();
+    let user_id = user.id;
+    let restricted = user.restricted();
+    let message = if pp_pending {
+        "Your pp couldn't be calculated right now, it will show up shortly!".to_string()
+    } else {
+        format!("Your lazer score is in! ({:.2}pp)", score.pp)
+    };
+
+    tokio::spawn(async move {
+        let _ = notify::notify(&outbox, user_id, &message).await;
+
+        if !restricted {
+            let _ = refresh_stats::refresh_stats(&outbox, user_id).await;
+        }
+    });
+
+    Ok(score)
+}
@@ -1,4 +1,153 @@
-use crate::models::{AimAssistType, LeaderboardScore};
+use anyhow::Result;
+
+use crate::{
+    config::Config,
+    constants::{LeaderboardType, Mods},
+    dto::leaderboard::GetScores,
+    infrastructure::{database::DbPoolManager, redis::RedisConnectionManager},
+    models::{AimAssistType, LeaderboardScore, Tournament, User},
+    repository::{self, leaderboard::LeaderboardFilter},
+    state::AppState,
+};
+
+/// the board the viewer gets. players who opted into it see their clan on
+/// the country tab, or the tournament running on the map on the global one.
+pub async fn resolve_leaderboard(
+    db: &DbPoolManager,
+    leaderboard: &GetScores,
+    user: &User,
+) -> (LeaderboardType, Option<Tournament>) {
+    let leaderboard_type = match LeaderboardType::from_i32(leaderboard.leaderboard_type) {
+        LeaderboardType::Country if user.clan_leaderboard && user.clan_id != 0 => {
+            LeaderboardType::Clan
+        },
+        LeaderboardType::Top if user.tournament_leaderboard => LeaderboardType::Tournament,
+        leaderboard_type => leaderboard_type,
+    };
+
+    resolve_tournament(db, leaderboard_type, &leaderboard.map_md5).await
+}
+
+/// the tournament running on the map for tournament boards.
+pub async fn resolve_tournament(
+    db: &DbPoolManager,
+    leaderboard_type: LeaderboardType,
+    map_md5: &str,
+) -> (LeaderboardType, Option<Tournament>) {
+    if leaderboard_type != LeaderboardType::Tournament {
+        return (leaderboard_type, None);
+    }
+
+    match repository::tournament::fetch_running_for_map(db, map_md5).await {
+        Ok(Some(tournament)) => (leaderboard_type, Some(tournament)),
+        // nothing running on the map, so it's the global board
+        _ => (LeaderboardType::Top, None),
+    }
+}
+
+/// (mods, mask) the mods board of `mods` is filtered with.
+pub fn mods_filter(config: &Config, mods: i32) -> (i32, i32) {
+    let merged_mods = Mods::from_acronyms(&config.leaderboard_merged_mods);
+
+    (
+        Mods::from_bits_retain(mods)
+            .leaderboard_equivalent(merged_mods)
+            .bits(),
+        Mods::all().difference(merged_mods).bits(),
+    )
+}
+
+/// filter of the board the viewer asked for.
+pub fn leaderboard_filter<'a>(
+    config: &Config,
+    leaderboard: &'a GetScores,
+    user: &'a User,
+    leaderboard_type: LeaderboardType,
+    tournament: Option<&'a Tournament>,
+    friend_ids: &'a [i32],
+) -> LeaderboardFilter<'a> {
+    let (mods, mods_mask) = mods_filter(config, leaderboard.mods);
+
+    LeaderboardFilter {
+        map_md5: &leaderboard.map_md5,
+        mode: leaderboard.mode().as_i32(),
+        user_id: user.id,
+        leaderboard_type,
+        mods,
+        mods_mask,
+        country: &user.country,
+        friend_ids,
+        clan_id: user.clan_id,
+        scoring_metric: user.preferred_metric(),
+        // tournaments are played on stable
+        lazer: leaderboard.lazer() && tournament.is_none(),
+        tournament,
+    }
+}
+
+/// top list of the requested board, out of redis when it's there.
+///
+/// friend & clan boards depend on who's in them, tournament boards change with
+/// every score in the window and restricted viewers get their own score mixed
+/// in, so none of them go through the cache.
+pub async fn fetch_leaderboard_scores(
+    state: &AppState,
+    filter: &LeaderboardFilter<'_>,
+    user: &User,
+    is_refx: bool,
+) -> Result<Vec<LeaderboardScore>> {
+    let cacheable = !matches!(
+        filter.leaderboard_type,
+        LeaderboardType::Friends | LeaderboardType::Clan | LeaderboardType::Tournament
+    ) && !user.restricted();
+    let size = state.config.leaderboard_size;
+    let key = cacheable.then(|| repository::leaderboard_cache::cache_key(filter, is_refx, size));
+
+    if let Some(key) = &key {
+        match repository::leaderboard_cache::fetch(&state.redis, key).await {
+            Ok(Some(scores)) => {
+                let _ = state.metrics.incr("leaderboard.cache", ["status:hit"]);
+                return Ok(scores);
+            },
+            Ok(None) => {
+                let _ = state.metrics.incr("leaderboard.cache", ["status:miss"]);
+            },
+            Err(e) => tracing::warn!("leaderboard cache read failed for {key}: {e}"),
+        }
+    }
+
+    let scores =
+        repository::leaderboard::fetch_leaderboard_scores(&state.db, filter, is_refx, size, 0)
+            .await?;
+
+    if let Some(key) = &key
+        && let Err(e) =
+            repository::leaderboard_cache::store(&state.redis, filter.map_md5, key, &scores).await
+    {
+        tracing::warn!("leaderboard cache write failed for {key}: {e}");
+    }
+
+    Ok(scores)
+}
+
+pub async fn invalidate_map(redis: &RedisConnectionManager, map_md5: &str) {
+    if let Err(e) = repository::leaderboard_cache::invalidate_map(redis, map_md5).await {
+        tracing::warn!("failed to invalidate the leaderboards of {map_md5}: {e}");
+    }
+}
+
+/// drops the boards of every map the user has a score on.
+pub async fn invalidate_user(
+    db: &DbPoolManager,
+    redis: &RedisConnectionManager,
+    user_id: i32,
+) -> Result<()> {
+    for map_md5 in repository::score::fetch_ranked_map_md5s(db, user_id).await? {
+        invalidate_map(redis, &map_md5).await;
+    }
+
+    Ok(())
+}
 
 pub fn format_score_line(score: &LeaderboardScore, rank: i32, is_refx: bool) -> String {
     if is_refx {
@@ -15,7 +164,7 @@ pub fn format_score_line(score: &LeaderboardScore, rank: i32, is_refx: bool) ->
         format!(
             "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|1|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
             score.id,
-            score.name,
+            score.display_name(),
             score.preferred_metric.round() as i64,
             score.max_combo,
             score.n50,
@@ -45,7 +194,7 @@ pub fn format_score_line(score: &LeaderboardScore, rank: i32, is_refx: bool) ->
         format!(
             "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|1",
             score.id,
-            score.name,
+            score.display_name(),
             score.preferred_metric.round() as i64,
             score.max_combo,
             score.n50,
@@ -1,6 +1,12 @@
 pub mod achievement;
+pub mod analysis;
 pub mod beatmap;
+pub mod lazer;
 pub mod leaderboard;
 pub mod password;
+pub mod pp_queue;
+pub mod replay;
 pub mod score;
 pub mod stats;
+pub mod tournament;
+pub mod validation;
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+
+use crate::{
+    constants::SubmissionStatus,
+    infrastructure::redis::publish::{announce, notify, refresh_stats},
+    repository,
+    state::AppState,
+    usecases::{
+        leaderboard::invalidate_map,
+        score::{calculate_placement, calculate_score_performance},
+        stats::{apply_ranked_best, recalculate},
+    },
+};
+
+const BATCH_SIZE: u32 = 50;
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+const BASE_BACKOFF_SECS: i64 = 15;
+const MAX_BACKOFF_SECS: i64 = 10 * 60;
+
+/// recalculates the scores that came in while no calculator was up.
+///
+/// entries are never given up on, they just wait longer between tries.
+pub async fn run(state: AppState) {
+    loop {
+        let entries = match repository::pp_queue::fetch_due(&state.db, BATCH_SIZE).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::error!("failed to fetch the pp queue: {e}");
+                Vec::new()
+            },
+        };
+
+        for (score_id, attempts) in entries {
+            if let Err(e) = process(&state, score_id).await {
+                let _ = state.metrics.incr("pp_queue.processed", ["status:retry"]);
+
+                let backoff = BASE_BACKOFF_SECS
+                    .saturating_mul(1 << attempts.clamp(0, 20))
+                    .min(MAX_BACKOFF_SECS);
+
+                tracing::warn!("pp calculation for score {score_id} failed again: {e}");
+
+                if let Err(e) = repository::pp_queue::reschedule(
+                    &state.db,
+                    score_id,
+                    &e.to_string(),
+                    Utc::now() + chrono::Duration::seconds(backoff),
+                )
+                .await
+                {
+                    tracing::error!("failed to reschedule score {score_id}: {e}");
+                }
+            }
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn process(state: &AppState, score_id: u64) -> Result<()> {
+    let Some(mut score) = repository::score::fetch_by_id(&state.db, score_id).await? else {
+        // score got wiped in the meantime, nothing left to fix.
+        let mut tx = state.db.begin().await?;
+        repository::pp_queue::delete(&mut tx, score_id).await?;
+        tx.commit().await?;
+
+        return Ok(());
+    };
+
+    let beatmap = repository::beatmap::fetch_by_md5(&state.config, &state.db, &score.map_md5)
+        .await?
+        .ok_or_else(|| anyhow!("beatmap {} not found", score.map_md5))?;
+
+    (score.pp, score.stars, score.hypothetical_pp) =
+        calculate_score_performance(&state.performance, &score, &beatmap).await?;
+
+    let mut tx = state.db.begin().await?;
+
+    // another instance got to it first
+    if !repository::pp_queue::delete(&mut tx, score.id).await? {
+        return Ok(());
+    }
+
+    repository::score::update_pp(&mut tx, score.id, score.pp).await?;
+
+    // the score went in with 0pp, so it couldn't have taken the best spot
+    // from an older score. see if it should have.
+    let mut promoted = false;
+    let mut prev_best = None;
+
+    if score.status() == SubmissionStatus::Submitted {
+        let best = repository::score::fetch_best(
+            &state.db,
+            score.userid,
+            &score.map_md5,
+            score.mode,
+            score.is_lazer(),
+        )
+        .await?;
+
+        if best.as_ref().is_none_or(|best| score.pp > best.pp) {
+            if let Some(best) = &best {
+                repository::score::update_status(
+                    &mut tx,
+                    best.id,
+                    SubmissionStatus::Submitted.as_i32(),
+                )
+                .await?;
+            }
+
+            score.status = SubmissionStatus::Best.as_i32();
+            repository::score::update_status(&mut tx, score.id, score.status).await?;
+
+            promoted = true;
+            prev_best = best;
+        }
+    }
+
+    let mut stats = None;
+
+    // lazer bests only rank on the lazer boards, not in the stats
+    if score.status() == SubmissionStatus::Best && beatmap.awards_ranked_pp() && !score.is_lazer() {
+        // locked, and only the ranked columns go back, so a submission
+        // landing in the meantime keeps its plays & playtime.
+        let mut user_stats =
+            repository::stats::fetch_for_update(&mut tx, &state.redis, score.userid, score.mode)
+                .await?
+                .ok_or_else(|| anyhow!("stats not found for user {}", score.userid))?;
+
+        // a best from the submission itself was already counted back then.
+        if promoted {
+            apply_ranked_best(&mut user_stats, &score, prev_best.as_ref());
+        }
+
+        recalculate(&mut tx, &mut user_stats).await?;
+        repository::stats::save_ranked(&mut tx, &user_stats).await?;
+
+        stats = Some(user_stats);
+    }
+
+    tx.commit().await?;
+
+    let _ = state.metrics.incr("pp_queue.processed", ["status:ok"]);
+
+    // lazer boards are built from every passed lazer score, not just bests
+    if score.status() == SubmissionStatus::Best || score.is_lazer() {
+        invalidate_map(&state.redis, &score.map_md5).await;
+    }
+
+    // the pp is in, everything below is best effort.
+
+    let Ok(Some(user)) = repository::user::fetch_by_id(&state.db, &score.userid).await else {
+        return Ok(());
+    };
+
+    // placements & announcements only go by the stable board
+    let best = score.status() == SubmissionStatus::Best && !score.is_lazer();
+
+    if best && beatmap.has_leaderboard() {
+        score.rank = calculate_placement(&state.db, &score).await;
+    }
+
+    if let Some(stats) = &stats {
+        let _ =
+            repository::stats::update_rank(&state.redis, stats, &user.country, user.restricted())
+                .await;
+    }
+
+    let message = if score.rank > 0 {
+        format!(
+            "Your pp on {} is in! #{} ({:.2}pp)",
+            beatmap.full_name(),
+            score.rank,
+            score.pp
+        )
+    } else {
+        format!(
+            "Your pp on {} is in! ({:.2}pp)",
+            beatmap.full_name(),
+            score.pp
+        )
+    };
+
+    let _ = notify::notify(&state.outbox, user.id, &message).await;
+
+    if !user.restricted() {
+        let _ = refresh_stats::refresh_stats(&state.outbox, user.id).await;
+
+        if best && score.rank == 1 && beatmap.has_leaderboard() {
+            let _ = announce::announce(&state.outbox, score.id).await;
+        }
+    }
+
+    tracing::info!(
+        "[{}] {} got their pp for score {} ({}pp)",
+        score.mode().as_str(),
+        user.name(),
+        score.id,
+        score.pp,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,457 @@
+use anyhow::Result;
+use md5::{Digest, Md5};
+
+use crate::{
+    constants::{GameMode, Mods, SubmissionStatus},
+    infrastructure::{database::DbPoolManager, redis::RedisConnectionManager},
+    models::{Beatmap, Score, User},
+    replay::{Fingerprint, Replay, ReplayHeader},
+    repository,
+};
+
+/// how far the replay duration can drift from the time the client reported.
+/// the client stops recording a bit after the last object, so keep it loose.
+const DURATION_TOLERANCE_MS: i32 = 10_000;
+const DURATION_TOLERANCE_RATIO: f32 = 0.10;
+
+/// anything past this is outside of every sane screen resolution.
+const MAX_CURSOR_COORDINATE: f32 = 100_000.0;
+
+/// the client records a frame every ~16.67ms of real time,
+/// so the audio time between two frames scales with the playback rate.
+const BASE_FRAME_TIME_MS: f64 = 1000.0 / 60.0;
+
+/// we need enough gameplay frames for the median to mean anything.
+const MIN_FRAMES_FOR_CLOCK_RATE: usize = 100;
+
+/// how far the estimated rate can drift from the reported one.
+const CLOCK_RATE_TOLERANCE: f64 = 0.05;
+
+/// how much of two fingerprints has to line up for them to be the same replay.
+const DUPLICATE_SIMILARITY: f32 = 0.9;
+
+/// a stolen replay is usually of a recent play, popular maps have way too many to compare against.
+const MAX_DUPLICATE_CANDIDATES: u32 = 500;
+
+/// a viewer only counts once per replay within this window.
+const REPLAY_VIEW_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// game version written into downloaded replays, we don't keep the real one.
+const OSR_VERSION: i32 = 20240101;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayViolation {
+    Undecodable,
+    Empty,
+    ImpossibleFrames { index: usize },
+    ModsMismatch { replay_mods: i32, score_mods: i32 },
+    DurationMismatch { replay_ms: i32, expected_ms: i32 },
+    FrameCountMismatch { frames: usize, judgements: i32 },
+    ClockRateMismatch { estimated: f64, reported: f64 },
+    ClockRateModsMismatch { reported: f64, mods_rate: f64 },
+}
+
+impl ReplayViolation {
+    pub fn metric(&self) -> &'static str {
+        match self {
+            ReplayViolation::Undecodable => "replay.undecodable",
+            ReplayViolation::Empty => "replay.empty_frames",
+            ReplayViolation::ImpossibleFrames { .. } => "replay.impossible_frames",
+            ReplayViolation::ModsMismatch { .. } => "replay.mods_mismatch",
+            ReplayViolation::DurationMismatch { .. } => "replay.duration_mismatch",
+            ReplayViolation::FrameCountMismatch { .. } => "replay.frame_count_mismatch",
+            ReplayViolation::ClockRateMismatch { .. } => "replay.clock_rate_mismatch",
+            ReplayViolation::ClockRateModsMismatch { .. } => "replay.clock_rate_mods_mismatch",
+        }
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            ReplayViolation::Undecodable => "undecodable replay".into(),
+            ReplayViolation::Empty => "replay without frames".into(),
+            ReplayViolation::ImpossibleFrames { index } => {
+                format!("impossible replay frame (#{index})")
+            },
+            ReplayViolation::ModsMismatch { replay_mods, score_mods } => {
+                format!("replay mods mismatch ({replay_mods} != {score_mods})")
+            },
+            ReplayViolation::DurationMismatch { replay_ms, expected_ms } => {
+                format!("replay duration mismatch ({replay_ms}ms != {expected_ms}ms)")
+            },
+            ReplayViolation::FrameCountMismatch { frames, judgements } => {
+                format!("replay frame count mismatch ({frames} frames < {judgements} hits)")
+            },
+            ReplayViolation::ClockRateMismatch { estimated, reported } => {
+                format!("replay clock rate mismatch ({estimated:.2}x != {reported:.2}x)")
+            },
+            ReplayViolation::ClockRateModsMismatch { reported, mods_rate } => {
+                format!("clock rate doesn't match mods ({reported:.2}x != {mods_rate:.2}x)")
+            },
+        }
+    }
+}
+
+/// decodes the replay the client uploaded and checks it against the submitted score.
+///
+/// `expected_ms` is either `st` or `ft` from the submission.
+pub fn decode_and_verify_replay(
+    data: &[u8],
+    score: &Score,
+    beatmap: &Beatmap,
+    expected_ms: i32,
+) -> Result<Replay, ReplayViolation> {
+    let replay = Replay::from_frames(data).map_err(|_| ReplayViolation::Undecodable)?;
+
+    verify_replay(&replay, score, beatmap, expected_ms)?;
+
+    Ok(replay)
+}
+
+pub fn verify_replay(
+    replay: &Replay,
+    score: &Score,
+    beatmap: &Beatmap,
+    expected_ms: i32,
+) -> Result<(), ReplayViolation> {
+    if replay.frames.is_empty() {
+        return Err(ReplayViolation::Empty);
+    }
+
+    check_frames(replay)?;
+    check_mods(replay, score, beatmap)?;
+    check_duration(replay, expected_ms)?;
+    check_frame_count(replay, score)?;
+
+    Ok(())
+}
+
+fn check_frames(replay: &Replay) -> Result<(), ReplayViolation> {
+    // frames before the audio starts (lead-in) can go backwards in time.
+    let mut started = false;
+
+    for (index, frame) in replay.frames.iter().enumerate() {
+        if !frame.x.is_finite()
+            || !frame.y.is_finite()
+            || frame.x.abs() > MAX_CURSOR_COORDINATE
+            || frame.y.abs() > MAX_CURSOR_COORDINATE
+        {
+            return Err(ReplayViolation::ImpossibleFrames { index });
+        }
+
+        if started && frame.delta < 0 {
+            return Err(ReplayViolation::ImpossibleFrames { index });
+        }
+
+        if frame.time > 0 {
+            started = true;
+        }
+    }
+
+    Ok(())
+}
+
+fn check_mods(replay: &Replay, score: &Score, beatmap: &Beatmap) -> Result<(), ReplayViolation> {
+    if let Some(header) = &replay.header
+        && header.mods != score.mods
+    {
+        return Err(ReplayViolation::ModsMismatch {
+            replay_mods: header.mods,
+            score_mods: score.mods,
+        });
+    }
+
+    // the uploaded frames doesn't have a header,
+    // but mania stores the pressed columns in `x` so we can
+    // at least check that against the key mods.
+    if score.mode() == GameMode::VN_MANIA {
+        let key_count = mania_key_count(score.mods()).or_else(|| {
+            (beatmap.mode == GameMode::VN_MANIA.as_vanilla() as i8).then_some(beatmap.cs as u32)
+        });
+
+        if let Some(key_count) = key_count.filter(|k| *k > 0) {
+            let pressed = replay.frames.iter().fold(0u32, |acc, f| acc | f.x as u32);
+
+            if pressed >> key_count != 0 {
+                return Err(ReplayViolation::ModsMismatch {
+                    replay_mods: pressed as i32,
+                    score_mods: score.mods,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_duration(replay: &Replay, expected_ms: i32) -> Result<(), ReplayViolation> {
+    if expected_ms <= 0 {
+        // older clients doesn't send these
+        return Ok(());
+    }
+
+    let replay_ms = replay.duration();
+    let tolerance =
+        DURATION_TOLERANCE_MS.max((expected_ms as f32 * DURATION_TOLERANCE_RATIO) as i32);
+
+    if (replay_ms - expected_ms).abs() > tolerance {
+        return Err(ReplayViolation::DurationMismatch { replay_ms, expected_ms });
+    }
+
+    Ok(())
+}
+
+fn check_frame_count(replay: &Replay, score: &Score) -> Result<(), ReplayViolation> {
+    // every hit needs at least one input frame,
+    // relax taiko/catch hits by itself so we can't tell there.
+    let judgements = match score.mode() {
+        GameMode::VN_TAIKO => score.n300 + score.n100,
+        GameMode::VN_MANIA => score.n300 + score.n100 + score.n50 + score.ngeki + score.nkatu,
+        mode if mode.as_vanilla() == 0 => score.n300 + score.n100 + score.n50,
+        _ => return Ok(()),
+    };
+
+    if (replay.frames.len() as i32) < judgements {
+        return Err(ReplayViolation::FrameCountMismatch {
+            frames: replay.frames.len(),
+            judgements,
+        });
+    }
+
+    Ok(())
+}
+
+/// estimates the rate the play actually ran at from the median frame delta.
+///
+/// only std & catch record frames at a fixed rate, taiko & mania write one
+/// per input change so their deltas follow the player's tapping instead.
+pub fn estimate_clock_rate(replay: &Replay, mode: GameMode) -> Option<f64> {
+    if !matches!(mode.as_vanilla(), 0 | 2) {
+        return None;
+    }
+
+    let mut deltas: Vec<i32> = replay
+        .frames
+        .iter()
+        .filter(|f| f.time > 0 && f.delta > 0)
+        .map(|f| f.delta)
+        .collect();
+
+    if deltas.len() < MIN_FRAMES_FOR_CLOCK_RATE {
+        return None;
+    }
+
+    deltas.sort_unstable();
+    let median = deltas[deltas.len() / 2] as f64;
+
+    Some(median / BASE_FRAME_TIME_MS)
+}
+
+/// the rate the client claims the play ran at.
+///
+/// same priority as `get_computed_playtime`.
+pub fn reported_clock_rate(score: &Score) -> f64 {
+    if score.clock_rate != -1.0 && score.clock_rate != 0.0 {
+        score.clock_rate
+    } else if score.uses_timewarp && score.timewarp_value > 0.0 {
+        (score.timewarp_value / 100.0) as f64
+    } else {
+        mods_clock_rate(score.mods())
+    }
+}
+
+/// compares the estimated playback rate against what the client reported,
+/// and what the client reported against the rate mods.
+pub fn verify_clock_rate(replay: &Replay, score: &Score) -> Result<(), ReplayViolation> {
+    let reported = reported_clock_rate(score);
+    let mods_rate = mods_clock_rate(score.mods());
+
+    // custom rates still need the matching rate mod (or none at all for 1.0x),
+    // timewarp is declared by the client so it can go either way.
+    let rate_mods_agree = score.uses_timewarp
+        || match mods_rate {
+            r if r > 1.0 => reported > 1.0,
+            r if r < 1.0 => reported < 1.0,
+            _ => (reported - 1.0).abs() <= CLOCK_RATE_TOLERANCE,
+        };
+
+    if !rate_mods_agree {
+        return Err(ReplayViolation::ClockRateModsMismatch { reported, mods_rate });
+    }
+
+    if let Some(estimated) = estimate_clock_rate(replay, score.mode())
+        && (estimated - reported).abs() / reported > CLOCK_RATE_TOLERANCE
+    {
+        return Err(ReplayViolation::ClockRateMismatch { estimated, reported });
+    }
+
+    Ok(())
+}
+
+/// looks for another player's replay on the same map that matches
+/// the fingerprint of the score's replay.
+///
+/// returns the id of the matching score.
+pub async fn find_duplicate_replay(
+    db: &DbPoolManager,
+    score: &Score,
+    fingerprint: &Fingerprint,
+) -> Result<Option<u64>> {
+    let others = repository::fingerprint::fetch_others_by_map(
+        db,
+        &score.map_md5,
+        score.userid,
+        MAX_DUPLICATE_CANDIDATES,
+    )
+    .await?;
+
+    let duplicate = others
+        .into_iter()
+        .find(|(_, _, data)| {
+            fingerprint.similarity(&Fingerprint::from_bytes(data)) >= DUPLICATE_SIMILARITY
+        })
+        .map(|(score_id, _, _)| score_id);
+
+    Ok(duplicate)
+}
+
+fn mods_clock_rate(mods: Mods) -> f64 {
+    if mods.contains(Mods::DOUBLETIME) || mods.contains(Mods::NIGHTCORE) {
+        1.5
+    } else if mods.contains(Mods::HALFTIME) {
+        0.75
+    } else {
+        1.0
+    }
+}
+
+fn mania_key_count(mods: Mods) -> Option<u32> {
+    [
+        (Mods::KEY1, 1),
+        (Mods::KEY2, 2),
+        (Mods::KEY3, 3),
+        (Mods::KEY4, 4),
+        (Mods::KEY5, 5),
+        (Mods::KEY6, 6),
+        (Mods::KEY7, 7),
+        (Mods::KEY8, 8),
+        (Mods::KEY9, 9),
+    ]
+    .into_iter()
+    .find(|(flag, _)| mods.contains(*flag))
+    .map(|(_, count)| if mods.contains(Mods::KEYCOOP) { count * 2 } else { count })
+}
+
+/// wraps the stored frames into a .osr that opens in the client & analysers.
+pub fn build_osr(score: &Score, user: &User, beatmap: &Beatmap, frames: &[u8]) -> Vec<u8> {
+    let count = |n: i32| n.clamp(0, u16::MAX as i32) as u16;
+
+    // the same hash the client writes, it isn't checked when watching anyway.
+    let replay_md5 = format!(
+        "{:x}",
+        Md5::digest(
+            format!(
+                "{}p{}o{}o{}t{}a{}r{}e{}y{}o{}u{}{}{}",
+                score.n100 + score.n300,
+                score.n50,
+                score.ngeki,
+                score.nkatu,
+                score.nmiss,
+                beatmap.md5,
+                score.max_combo,
+                if score.perfect { "True" } else { "False" },
+                user.name,
+                score.score,
+                score.grade,
+                score.mods,
+                "True",
+            )
+            .as_bytes()
+        )
+    );
+
+    let header = ReplayHeader {
+        mode: score.mode().as_vanilla() as u8,
+        version: OSR_VERSION,
+        map_md5: beatmap.md5.clone(),
+        player_name: user.name.clone(),
+        replay_md5,
+        n300: count(score.n300),
+        n100: count(score.n100),
+        n50: count(score.n50),
+        ngeki: count(score.ngeki),
+        nkatu: count(score.nkatu),
+        nmiss: count(score.nmiss),
+        score: score.score,
+        max_combo: count(score.max_combo),
+        perfect: score.perfect,
+        mods: score.mods,
+        life_bar: Vec::new(),
+        timestamp: ReplayHeader::ticks_from_unix_millis(score.play_time.timestamp_millis()),
+        online_score_id: score.id as i64,
+    };
+
+    header.write_osr(frames)
+}
+
+/// whether `viewer` gets to watch the replay of `score`, set by `owner`.
+/// staff can watch everything, players can always watch their own.
+pub fn can_view_replay(viewer: Option<&User>, owner: &User, score: &Score) -> bool {
+    if let Some(viewer) = viewer
+        && (viewer.staff() || viewer.id == owner.id)
+    {
+        return true;
+    }
+
+    !owner.restricted() && !owner.private_replays && score.status() == SubmissionStatus::Best
+}
+
+/// the score & its player, if the viewer is allowed to watch its replay.
+/// hidden & missing replays are both `None` so callers can't tell them apart.
+pub async fn fetch_viewable_replay(
+    db: &DbPoolManager,
+    viewer: Option<&User>,
+    score_id: u64,
+) -> Result<Option<(Score, User)>> {
+    let Some(score) = repository::score::fetch_by_id(db, score_id).await? else {
+        return Ok(None);
+    };
+
+    let Some(owner) = repository::user::fetch_by_id(db, &score.userid).await? else {
+        return Ok(None);
+    };
+
+    if !can_view_replay(viewer, &owner, &score) {
+        return Ok(None);
+    }
+
+    Ok(Some((score, owner)))
+}
+
+/// counts a view of someone else's replay, refreshing it doesn't count again.
+pub async fn record_replay_view(
+    db: &DbPoolManager,
+    redis: &RedisConnectionManager,
+    viewer_id: i32,
+    score: &Score,
+) -> Result<bool> {
+    if viewer_id == score.userid
+        || !repository::replay_view::mark_viewed(
+            redis,
+            viewer_id,
+            score.id,
+            REPLAY_VIEW_WINDOW_SECS,
+        )
+        .await?
+    {
+        return Ok(false);
+    }
+
+    let mut tx = db.begin().await?;
+
+    repository::replay_view::increment(&mut tx, score.id, score.mode).await?;
+    repository::stats::increment_replay_views(&mut tx, score.userid, score.mode).await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
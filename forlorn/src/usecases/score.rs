@@ -1,16 +1,17 @@
 use anyhow::Result;
 use base64::prelude::*;
+use md5::{Digest, Md5};
 use simple_rijndael::{Errors, impls::RijndaelCbc, paddings::Pkcs7Padding};
 use sqlx::types::Json;
 use webhook::{Author, Embed, Footer, Thumbnail, Webhook};
 
 use crate::{
-    config::OmajinaiConfig,
     constants::{GameMode, Grade, Mods, SubmissionStatus},
     dto::submission::ScoreSubmission,
     infrastructure::{
-        database::DbPoolManager,
-        omajinai::{PerformanceRequest, calculate_pp},
+        database::{DbConnection, DbPoolManager},
+        omajinai::PerformanceRequest,
+        performance::Performance,
     },
     models::{AimAssistType, Beatmap, Score, User},
     repository,
@@ -24,7 +25,7 @@ pub fn decrypt_score_data(
     client_hash_b64: &[u8],
     iv_b64: &[u8],
     osu_version: &str,
-) -> Result<(Vec<String>, String), Errors> {
+) -> Result<(Vec<String>, String, String), Errors> {
     let aes = RijndaelCbc::<Pkcs7Padding>::new(
         format!("osu!-scoreburgr---------{osu_version}").as_bytes(),
         32,
@@ -65,7 +66,43 @@ pub fn decrypt_score_data(
             full_path.to_string()
         });
 
-    Ok((score_data, osu_path_md5))
+    Ok((score_data, client_hash_decoded, osu_path_md5))
+}
+
+/// rebuilds the online checksum the same way the client does,
+/// so edited score strings can't just reuse the original one.
+pub fn calculate_online_checksum(
+    score: &Score,
+    username: &str,
+    osu_version: &str,
+    client_hash: &str,
+    storyboard_md5: &str,
+) -> String {
+    let bool_str = |b: bool| if b { "True" } else { "False" };
+
+    let data = format!(
+        "chickenmcnuggets{}o15{}{}smustard{}{}uu{}{}{}{}{}{}{}Q{}{}{}{}{}{}",
+        score.n100 + score.n300,
+        score.n50,
+        score.ngeki,
+        score.nkatu,
+        score.nmiss,
+        score.map_md5,
+        score.max_combo,
+        bool_str(score.perfect),
+        username,
+        score.score,
+        score.grade,
+        score.mods,
+        bool_str(score.passed),
+        score.mode().as_vanilla(),
+        osu_version,
+        score.play_time.format("%y%m%d%H%M%S"),
+        client_hash,
+        storyboard_md5,
+    );
+
+    format!("{:x}", Md5::digest(data.as_bytes()))
 }
 
 pub fn calculate_accuracy(score: &Score) -> f32 {
@@ -114,10 +151,69 @@ pub fn calculate_accuracy(score: &Score) -> f32 {
     }
 }
 
+/// the grade the client should have shown, stable's rules.
+pub fn calculate_grade(score: &Score) -> Grade {
+    let mods = score.mods();
+    let silver = mods.contains(Mods::HIDDEN) || mods.contains(Mods::FLASHLIGHT);
+
+    let grade = match score.mode().as_vanilla() {
+        0 | 1 => {
+            let total = (score.n300 + score.n100 + score.n50 + score.nmiss) as f32;
+            if total == 0.0 {
+                return Grade::D;
+            }
+
+            let ratio300 = score.n300 as f32 / total;
+            let ratio50 = score.n50 as f32 / total;
+
+            if score.n300 as f32 == total {
+                Grade::X
+            } else if ratio300 > 0.9 && ratio50 <= 0.01 && score.nmiss == 0 {
+                Grade::S
+            } else if (ratio300 > 0.8 && score.nmiss == 0) || ratio300 > 0.9 {
+                Grade::A
+            } else if (ratio300 > 0.7 && score.nmiss == 0) || ratio300 > 0.8 {
+                Grade::B
+            } else if ratio300 > 0.6 {
+                Grade::C
+            } else {
+                Grade::D
+            }
+        },
+        2 => match score.acc {
+            acc if acc >= 100.0 => Grade::X,
+            acc if acc > 98.0 => Grade::S,
+            acc if acc > 94.0 => Grade::A,
+            acc if acc > 90.0 => Grade::B,
+            acc if acc > 85.0 => Grade::C,
+            _ => Grade::D,
+        },
+        _ => match score.acc {
+            acc if acc >= 100.0 => Grade::X,
+            acc if acc > 95.0 => Grade::S,
+            acc if acc > 90.0 => Grade::A,
+            acc if acc > 80.0 => Grade::B,
+            acc if acc > 70.0 => Grade::C,
+            _ => Grade::D,
+        },
+    };
+
+    match grade {
+        Grade::X if silver => Grade::XH,
+        Grade::S if silver => Grade::SH,
+        grade => grade,
+    }
+}
+
 pub async fn calculate_status(db: &DbPoolManager, new_score: &mut Score) -> Result<Option<Score>> {
-    let previous_best =
-        repository::score::fetch_best(db, new_score.userid, &new_score.map_md5, new_score.mode)
-            .await?;
+    let previous_best = repository::score::fetch_best(
+        db,
+        new_score.userid,
+        &new_score.map_md5,
+        new_score.mode,
+        new_score.is_lazer(),
+    )
+    .await?;
 
     match previous_best {
         Some(mut prev_best) => {
@@ -149,14 +245,17 @@ pub async fn calculate_placement(db: &DbPoolManager, score: &Score) -> u32 {
     num_better_scores.unwrap_or_default()
 }
 
-pub async fn update_any_preexisting_personal_best(db: &DbPoolManager, score: &Score) -> () {
-    let _ = repository::score::update_preexisting_personal_best(db, score).await;
+pub async fn update_any_preexisting_personal_best(
+    conn: &mut DbConnection,
+    score: &Score,
+) -> Result<()> {
+    repository::score::update_preexisting_personal_best(conn, score).await
 }
 
 #[allow(clippy::too_many_arguments)]
 pub async fn calculate_performance(
-    config: &OmajinaiConfig,
-    beatmap_id: i32,
+    performance: &Performance,
+    beatmap: &Beatmap,
     mode: i32,
     mods: i32,
     max_combo: i32,
@@ -171,7 +270,7 @@ pub async fn calculate_performance(
     nkatu: Option<i32>,
 ) -> (f32, f32, f32) {
     let request = PerformanceRequest {
-        beatmap_id,
+        beatmap_id: beatmap.id,
         mode,
         mods,
         max_combo,
@@ -186,12 +285,40 @@ pub async fn calculate_performance(
         nkatu,
     };
 
-    match calculate_pp(config, &request).await {
+    match performance.calculate(beatmap, &request).await {
         Ok(result) => (result.pp, result.stars, result.hypothetical_pp),
         Err(_) => (0.0, 0.0, 0.0), // TODO: raise for error instead setting to 0? but it will broke submission..
     }
 }
 
+/// same as `calculate_performance`, but lets the caller know when every calculator is down
+/// instead of pretending the score is worth 0pp.
+pub async fn calculate_score_performance(
+    performance: &Performance,
+    score: &Score,
+    beatmap: &Beatmap,
+) -> Result<(f32, f32, f32)> {
+    let request = PerformanceRequest {
+        beatmap_id: beatmap.id,
+        mode: score.mode,
+        mods: score.mods,
+        max_combo: score.max_combo,
+        accuracy: score.acc,
+        miss_count: score.nmiss,
+        legacy_score: score.score,
+        clock_rate: score.clock_rate,
+        n300: Some(score.n300),
+        n100: Some(score.n100),
+        n50: Some(score.n50),
+        ngeki: Some(score.ngeki),
+        nkatu: Some(score.nkatu),
+    };
+
+    let result = performance.calculate(beatmap, &request).await?;
+
+    Ok((result.pp, result.stars, result.hypothetical_pp))
+}
+
 /// This xp calculation that was supposed to
 /// replace "Performance Point" for the cheat/cheatcheat mode.
 /// It was implemented by kaupec1 when the server was a cheat only (early days),
@@ -1,14 +1,14 @@
 use anyhow::Result;
 
 use crate::{
-    constants::Mods,
-    infrastructure::database::DbPoolManager,
+    constants::{Grade, Mods, SubmissionStatus},
+    infrastructure::database::DbConnection,
     models::{Beatmap, Score, Stats},
     repository,
 };
 
-pub async fn recalculate(db: &DbPoolManager, stats: &mut Stats) -> Result<()> {
-    let scores = repository::stats::fetch_total_scores(db, stats).await?;
+pub async fn recalculate(conn: &mut DbConnection, stats: &mut Stats) -> Result<()> {
+    let scores = repository::stats::fetch_total_scores(conn, stats).await?;
 
     let mut total_acc = 0.0;
     let mut total_pp = 0.0;
@@ -26,13 +26,13 @@ pub async fn recalculate(db: &DbPoolManager, stats: &mut Stats) -> Result<()> {
     stats.acc =
         (total_acc * (100.0 / (20.0 * (1.0 - 0.95_f32.powi((last_idx + 1) as i32))))) / 100.0;
 
-    stats.pp = (total_pp + calculate_bonus(db, stats).await?) as u32;
+    stats.pp = (total_pp + calculate_bonus(conn, stats).await?) as u32;
 
     Ok(())
 }
 
-pub async fn calculate_bonus(db: &DbPoolManager, stats: &Stats) -> Result<f32> {
-    let result = repository::stats::fetch_bonus_count(db, stats).await?;
+pub async fn calculate_bonus(conn: &mut DbConnection, stats: &Stats) -> Result<f32> {
+    let result = repository::stats::fetch_bonus_count(conn, stats).await?;
 
     let count = result.min(1000);
     let bonus_pp = 416.6667 * (1.0 - (0.995_f32.powi(count)));
@@ -40,6 +40,68 @@ pub async fn calculate_bonus(db: &DbPoolManager, stats: &Stats) -> Result<f32> {
     Ok(bonus_pp)
 }
 
+/// counts a submitted score into the stats & saves them, returns whether it was a new ranked best.
+/// lazer bests only rank on the lazer boards, so they stay out of the ranked totals.
+pub async fn apply_submission(
+    conn: &mut DbConnection,
+    stats: &mut Stats,
+    score: &Score,
+    beatmap: &Beatmap,
+    prev_best: Option<&Score>,
+) -> Result<bool> {
+    stats.playtime += get_computed_playtime(score, beatmap);
+    stats.plays += 1;
+    stats.tscore += score.score as u64;
+    stats.total_hits += score.total_hits();
+    stats.xp += score.xp.round() as i32;
+
+    let mut ranked_best = false;
+
+    if score.passed && beatmap.has_leaderboard() {
+        stats.max_combo = stats.max_combo.max(score.max_combo as u32);
+
+        if beatmap.awards_ranked_pp()
+            && score.status() == SubmissionStatus::Best
+            && !score.is_lazer()
+        {
+            ranked_best = true;
+
+            apply_ranked_best(stats, score, prev_best);
+
+            if score.pp > 0.0 {
+                recalculate(conn, stats).await?;
+            }
+        }
+    }
+
+    repository::stats::save(conn, stats).await?;
+
+    Ok(ranked_best)
+}
+
+/// counts a new ranked best into the stats, taking off what the previous best gave.
+pub fn apply_ranked_best(stats: &mut Stats, score: &Score, prev_best: Option<&Score>) {
+    let mut additional_rscore = score.score as i64;
+    if let Some(pb) = prev_best {
+        additional_rscore -= pb.score as i64;
+
+        if score.grade() != pb.grade() {
+            if score.grade() >= Grade::A {
+                stats.increment_grade(score.grade());
+            }
+
+            if pb.grade() >= Grade::A {
+                stats.decrement_grade(pb.grade());
+            }
+        }
+    } else if score.grade() >= Grade::A {
+        stats.increment_grade(score.grade());
+    }
+
+    // a best by pp can still have less score than the one it replaced
+    stats.rscore = stats.rscore.saturating_add_signed(additional_rscore);
+}
+
 pub fn get_computed_playtime(score: &Score, beatmap: &Beatmap) -> u32 {
     if score.passed {
         beatmap.total_length as u32
@@ -0,0 +1,20 @@
+use anyhow::Result;
+
+use crate::{
+    dto::v1::tournament::CreateTournament, infrastructure::database::DbPoolManager, repository,
+};
+
+/// creates the tournament along with its pool, returns its id.
+pub async fn create_tournament(db: &DbPoolManager, tournament: &CreateTournament) -> Result<u32> {
+    let mut tx = db.begin().await?;
+
+    let tournament_id = repository::tournament::insert(&mut tx, tournament).await?;
+
+    for map_md5 in &tournament.maps {
+        repository::tournament::insert_map(&mut tx, tournament_id, map_md5).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(tournament_id)
+}
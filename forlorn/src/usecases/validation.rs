@@ -0,0 +1,215 @@
+use webhook::Webhook;
+
+use crate::{
+    config::ScoreValidationConfig,
+    constants::{Grade, ViolationAction},
+    infrastructure::redis::publish::restrict,
+    models::{Beatmap, Score, User},
+    osu_file::OsuFile,
+    state::AppState,
+    usecases::score::calculate_grade,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreViolation {
+    JudgementCountMismatch { judgements: i32, objects: i32 },
+    ComboExceedsMap { combo: i32, map_combo: i32 },
+    PerfectMismatch { perfect: bool, misses: i32, combo: i32 },
+    GradeMismatch { submitted: Grade, expected: Grade },
+    ChecksumMismatch { submitted: String, expected: String },
+}
+
+impl ScoreViolation {
+    pub fn metric(&self) -> &'static str {
+        match self {
+            ScoreViolation::JudgementCountMismatch { .. } => "score.judgement_count_mismatch",
+            ScoreViolation::ComboExceedsMap { .. } => "score.combo_exceeds_map",
+            ScoreViolation::PerfectMismatch { .. } => "score.perfect_mismatch",
+            ScoreViolation::GradeMismatch { .. } => "score.grade_mismatch",
+            ScoreViolation::ChecksumMismatch { .. } => "score.checksum_mismatch",
+        }
+    }
+
+    /// what the config says to do about it, anything unknown only gets flagged.
+    pub fn action(&self, config: &ScoreValidationConfig) -> ViolationAction {
+        let action = match self {
+            ScoreViolation::JudgementCountMismatch { .. } => &config.judgement_count,
+            ScoreViolation::ComboExceedsMap { .. } => &config.combo,
+            ScoreViolation::PerfectMismatch { .. } => &config.perfect,
+            ScoreViolation::GradeMismatch { .. } => &config.grade,
+            ScoreViolation::ChecksumMismatch { .. } => &config.checksum,
+        };
+
+        ViolationAction::parse(action).unwrap_or(ViolationAction::Flag)
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            ScoreViolation::JudgementCountMismatch { judgements, objects } => {
+                format!("judgement count mismatch ({judgements} != {objects} objects)")
+            },
+            ScoreViolation::ComboExceedsMap { combo, map_combo } => {
+                format!("combo exceeds the map ({combo}x > {map_combo}x)")
+            },
+            ScoreViolation::PerfectMismatch { perfect, misses, combo } => {
+                format!("perfect flag mismatch (perfect={perfect}, {misses} misses, {combo}x)")
+            },
+            ScoreViolation::GradeMismatch { submitted, expected } => {
+                format!("grade mismatch ({submitted:?} != {expected:?})")
+            },
+            ScoreViolation::ChecksumMismatch { submitted, expected } => {
+                format!("online checksum mismatch ({submitted} != {expected})")
+            },
+        }
+    }
+}
+
+/// cross-checks the hits of a passed score against the map.
+///
+/// the judgement count is only checked when we have the .osu and
+/// it's not a convert, since converts changes the object count.
+pub fn validate_hit_counts(
+    score: &Score,
+    beatmap: &Beatmap,
+    osu: Option<&OsuFile>,
+) -> Vec<ScoreViolation> {
+    let mut violations = Vec::new();
+    let mode = score.mode().as_vanilla();
+
+    if let Some(osu) = osu
+        && osu.general.mode as i32 == mode
+        && let Some((judgements, objects)) = judgements_and_objects(score, osu)
+        && judgements != objects
+    {
+        violations.push(ScoreViolation::JudgementCountMismatch { judgements, objects });
+    }
+
+    // the map's max combo is for its own mode, converts can go past it
+    if beatmap.mode as i32 == mode && beatmap.max_combo > 0 && score.max_combo > beatmap.max_combo {
+        violations.push(ScoreViolation::ComboExceedsMap {
+            combo: score.max_combo,
+            map_combo: beatmap.max_combo,
+        });
+    }
+
+    // slider ends can be dropped on a perfect play, so only a full combo
+    // without the flag is suspicious the other way around.
+    let full_combo = mode == 0
+        && beatmap.max_combo > 0
+        && score.nmiss == 0
+        && score.max_combo >= beatmap.max_combo;
+
+    if (score.perfect && score.nmiss > 0) || (!score.perfect && full_combo) {
+        violations.push(ScoreViolation::PerfectMismatch {
+            perfect: score.perfect,
+            misses: score.nmiss,
+            combo: score.max_combo,
+        });
+    }
+
+    let expected = calculate_grade(score);
+    if score.grade() != expected {
+        violations.push(ScoreViolation::GradeMismatch { submitted: score.grade(), expected });
+    }
+
+    violations
+}
+
+/// reports the violations & does what the config says for each of them.
+///
+/// returns whether the score should be rejected.
+pub async fn handle_violations(
+    state: &AppState,
+    user: &User,
+    score: &Score,
+    beatmap: &Beatmap,
+    violations: &[ScoreViolation],
+) -> bool {
+    if violations.is_empty() {
+        return false;
+    }
+
+    let reasons = |action: ViolationAction| {
+        violations
+            .iter()
+            .filter(|v| v.action(&state.config.score_validation) == action)
+            .map(|v| v.reason())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    for violation in violations {
+        let _ = state.metrics.incr(violation.metric(), ["status:ok"]);
+    }
+
+    tracing::warn!(
+        "[{}] {} submitted a score that failed validation on {}: {}",
+        score.mode().as_str(),
+        user.name(),
+        beatmap.id,
+        violations
+            .iter()
+            .map(|v| v.reason())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let restrict_reason = reasons(ViolationAction::Restrict);
+    if !restrict_reason.is_empty() {
+        let _ = restrict::restrict(&state.outbox, user.id, &restrict_reason).await;
+    }
+
+    let flag_reason = reasons(ViolationAction::Flag);
+    if !flag_reason.is_empty() {
+        let webhook = Webhook::new(&state.config.webhook.debug).content(format!(
+            "[{}] {} failed score validation on {} ({}) [300={}|100={}|50={}|geki={}|katu={}|miss={}|combo={}]",
+            score.mode().as_str(),
+            user.name(),
+            beatmap.full_name(),
+            flag_reason,
+            score.n300,
+            score.n100,
+            score.n50,
+            score.ngeki,
+            score.nkatu,
+            score.nmiss,
+            score.max_combo,
+        ));
+
+        tokio::spawn(async move {
+            let _ = webhook.post().await;
+        });
+    }
+
+    !reasons(ViolationAction::Reject).is_empty()
+}
+
+/// judgements the score has and the ones the map gives out.
+fn judgements_and_objects(score: &Score, osu: &OsuFile) -> Option<(i32, i32)> {
+    match score.mode().as_vanilla() {
+        0 => Some((
+            score.n300 + score.n100 + score.n50 + score.nmiss,
+            osu.object_count() as i32,
+        )),
+        // drumrolls & swells doesn't give out judgements
+        1 => Some((
+            score.n300 + score.n100 + score.nmiss,
+            osu.circle_count() as i32,
+        )),
+        // tiny droplets (50s & katus) aren't worth combo, the rest are
+        2 => Some((
+            score.n300 + score.n100 + score.nmiss,
+            osu.max_combo() as i32,
+        )),
+        // lazer judges the head & tail of a hold on their own
+        3 => {
+            let holds = if score.is_lazer() { osu.hold_count() * 2 } else { osu.hold_count() };
+
+            Some((
+                score.ngeki + score.n300 + score.nkatu + score.n100 + score.n50 + score.nmiss,
+                (osu.circle_count() + holds) as i32,
+            ))
+        },
+        _ => None,
+    }
+}
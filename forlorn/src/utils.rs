@@ -9,7 +9,7 @@ use tokio::signal::{
 
 use crate::{
     dto::{error::GetError, screenshot::ScreenshotUpload, submission::ScoreSubmission},
-    models::{Beatmap, LeaderboardScore, MapleAimAssistValues, PersonalBest, Score, Stats, User},
+    models::{Beatmap, LeaderboardScore, MapleAimAssistValues, PersonalBest, Score, Stats},
     repository,
     state::AppState,
     usecases::{achievement::check_and_unlock_achievements, leaderboard::format_score_line},
@@ -170,8 +170,14 @@ pub async fn build_submission_charts(
     ));
     charts.push("chartName:Beatmap Ranking".to_string());
 
-    if let Ok(Some(prev_best)) =
-        repository::score::fetch_best(&state.db, score.userid, &beatmap.md5, score.mode).await
+    if let Ok(Some(prev_best)) = repository::score::fetch_best(
+        &state.db,
+        score.userid,
+        &beatmap.md5,
+        score.mode,
+        score.is_lazer(),
+    )
+    .await
     {
         charts.push(chart_entry("rank", prev_best.rank, score.rank));
         charts.push(chart_entry("rankedScore", prev_best.score, score.score));
@@ -285,14 +291,6 @@ pub async fn save_screenshot(state: &AppState, screenshot_data: Vec<u8>) -> Stri
     file_name
 }
 
-pub async fn build_display_name(user: &User, state: &AppState) -> String {
-    if let Ok(Some(clan)) = repository::clan::fetch_by_id(&state.db, user.clan_id).await {
-        return format!("[{}] {}", clan.tag, user.name);
-    }
-
-    user.name.clone()
-}
-
 pub async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
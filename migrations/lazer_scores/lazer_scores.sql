@@ -0,0 +1,15 @@
+create table lazer_scores
+(
+    score_id bigint unsigned not null primary key,
+    checksum char(32) not null,
+
+    total_score bigint not null,
+    classic_total_score bigint not null,
+
+    mods json not null,
+    statistics json not null,
+
+    created_at timestamp not null default current_timestamp,
+
+    unique index idx_checksum (checksum)
+);
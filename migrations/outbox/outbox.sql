@@ -0,0 +1,22 @@
+create table outbox_events
+(
+    id bigint unsigned not null auto_increment primary key,
+
+    channel varchar(64) not null,
+    payload text not null,
+
+    -- 0 = pending, 1 = delivered, 2 = failed
+    status tinyint not null default 0,
+    attempts int not null default 0,
+    last_error varchar(512) null,
+
+    next_attempt_at timestamp not null default current_timestamp,
+    claimed_by varchar(64) null,
+    claimed_until timestamp null,
+
+    created_at timestamp not null default current_timestamp,
+    delivered_at timestamp null,
+
+    index idx_status_next_attempt (status, next_attempt_at),
+    index idx_created_at (created_at)
+);
@@ -0,0 +1,12 @@
+create table pp_queue
+(
+    score_id bigint unsigned not null primary key,
+
+    attempts int not null default 0,
+    last_error varchar(512) null,
+    next_attempt_at timestamp not null default current_timestamp,
+
+    created_at timestamp not null default current_timestamp,
+
+    index idx_next_attempt_at (next_attempt_at)
+);
@@ -0,0 +1,2 @@
+alter table users
+    add column private_replays tinyint(1) not null default 0;
@@ -0,0 +1,11 @@
+create table replay_views
+(
+    score_id bigint unsigned not null primary key,
+    mode tinyint not null,
+
+    views int unsigned not null default 0,
+
+    updated_at timestamp not null default current_timestamp on update current_timestamp,
+
+    index idx_mode_views (mode, views)
+);
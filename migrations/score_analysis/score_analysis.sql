@@ -0,0 +1,20 @@
+create table score_analysis
+(
+    score_id bigint unsigned not null primary key,
+    mode tinyint not null,
+
+    unstable_rate float null,
+    press_count int not null default 0,
+    press_interval_mean float null,
+    press_interval_stddev float null,
+    hold_time_mean float null,
+    hold_time_variance float null,
+    snap_count int not null default 0,
+
+    flagged tinyint(1) not null default 0,
+    flag_reasons varchar(512) null,
+
+    created_at timestamp not null default current_timestamp,
+
+    index idx_flagged (flagged)
+);
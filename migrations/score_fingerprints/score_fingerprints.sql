@@ -0,0 +1,12 @@
+create table score_fingerprints
+(
+    score_id bigint unsigned not null primary key,
+    map_md5 char(32) not null,
+    userid int not null,
+
+    fingerprint mediumblob not null,
+
+    created_at timestamp not null default current_timestamp,
+
+    index idx_map_md5_created_at (map_md5, created_at)
+);
@@ -0,0 +1,2 @@
+alter table users
+    add column tournament_leaderboard tinyint(1) not null default 0;
@@ -0,0 +1,24 @@
+create table tournaments
+(
+    id int unsigned not null auto_increment primary key,
+    name varchar(64) not null,
+
+    -- exact mods every score has to be set with, any when null
+    mods int null,
+
+    starts_at datetime not null,
+    ends_at datetime not null,
+
+    created_at timestamp not null default current_timestamp,
+
+    index idx_window (starts_at, ends_at)
+);
+
+create table tournament_maps
+(
+    tournament_id int unsigned not null,
+    map_md5 char(32) not null,
+
+    primary key (tournament_id, map_md5),
+    index idx_map_md5 (map_md5)
+);
@@ -49,6 +49,9 @@ impl Storage {
     fn replay_file(&self, score_id: u64) -> PathBuf {
         self.replay_path.join(format!("{score_id}.osr"))
     }
+    fn lazer_replay_file(&self, score_id: u64) -> PathBuf {
+        self.replay_path.join(format!("{score_id}.osr-lazer"))
+    }
     fn screenshot_file(&self, name_with_ext: &str) -> PathBuf {
         self.screenshot_path.join(name_with_ext)
     }
@@ -59,6 +62,9 @@ impl Storage {
     fn replay_key(&self, score_id: u64) -> String {
         format!("osr/{score_id}.osr")
     }
+    fn lazer_replay_key(&self, score_id: u64) -> String {
+        format!("osr-lazer/{score_id}")
+    }
     fn screenshot_key(&self, name_with_ext: &str) -> String {
         format!("ss/{name_with_ext}")
     }
@@ -117,6 +123,35 @@ impl Storage {
         }
     }
 
+    /// the extra data the refx client sends next to the replay,
+    /// kept apart so the .osr stays readable.
+    pub async fn save_lazer_replay(&self, score_id: u64, data: &[u8]) -> Result<()> {
+        if let Some(r2) = &self.r2 {
+            r2.upload(
+                &self.lazer_replay_key(score_id),
+                data,
+                None,
+                Some("application/octet-stream"),
+            )
+            .await;
+        } else {
+            fs::write(self.lazer_replay_file(score_id), data)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn load_lazer_replay(&self, score_id: u64) -> Result<Vec<u8>> {
+        if let Some(r2) = &self.r2 {
+            match r2.get(&self.lazer_replay_key(score_id)).await {
+                Some(payload) => Ok(payload),
+                None => Ok(fs::read(self.lazer_replay_file(score_id))?),
+            }
+        } else {
+            Ok(fs::read(self.lazer_replay_file(score_id))?)
+        }
+    }
+
     pub async fn save_screenshot(&self, name_with_ext: &str, data: &[u8]) -> Result<()> {
         if let Some(r2) = &self.r2 {
             let content_type = if name_with_ext.to_lowercase().ends_with(".jpeg") {